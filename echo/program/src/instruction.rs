@@ -1,4 +1,9 @@
 use borsh::{BorshDeserialize, BorshSerialize};
+use solana_program::instruction::{AccountMeta, Instruction};
+use solana_program::pubkey::Pubkey;
+use solana_program::system_program;
+
+use crate::pda::{find_authorized_buffer_address, find_vending_machine_buffer_address};
 
 #[derive(BorshSerialize, BorshDeserialize, Debug, Clone)]
 pub enum EchoInstruction {
@@ -24,7 +29,7 @@ pub enum EchoInstruction {
     /// | index | writable | signer | description                                                              |
     /// |-------|----------|--------|--------------------------------------------------------------------------|
     /// | 0     | ✅       | ❌     | authorized_buffer: PDA of Echo Program that only `authority` can write to |
-    /// | 1     | ❌       | ✅     | authority: Pubkey with sole write access to `authorized_buffer`           |
+    /// | 1     | ✅       | ✅     | authority: Pubkey with sole write access to `authorized_buffer`           |
     /// | 2     | ❌       | ❌     | system_program: Used to allocate the buffer                               |
     InitializeAuthorizedEcho {
         buffer_seed: u64,
@@ -58,7 +63,7 @@ pub enum EchoInstruction {
     /// |-------|----------|--------|-----------------------------------------------------------------------------------------------------|
     /// | 0     | ✅       | ❌     | vending_machine_buffer: PDA of the Echo Program that only holders of a particular token can write to |
     /// | 1     | ❌       | ❌     | vending_machine_mint: Pubkey with sole write access to `authorized_buffer`                           |
-    /// | 2     | ❌       | ✅     | payer: Pubkey that allocates the `vending_machine_buffer`                                            |
+    /// | 2     | ✅       | ✅     | payer: Pubkey that allocates the `vending_machine_buffer`                                            |
     /// | 3     | ❌       | ❌     | system_program: Used to allocate the buffer                                                          |
     InitializeVendingMachineEcho {
         // Number of tokens required change the buffer
@@ -88,7 +93,90 @@ pub enum EchoInstruction {
     /// | 0     | ✅       | ❌     | vending_machine_buffer: PDA of the Echo Program that only holders of a particular token can write to |
     /// | 1     | ❌       | ✅     | user: This is authority of the token account that is using the vending machine                       |
     /// | 2     | ✅       | ❌     | user_token_account: This is the token account that will pay for the use of the vending machine       |
-    /// | 3     | ❌       | ❌     | vending_machine_mint: This is the token mint that is accepted by the `vending_machine_buffer`        |
-    /// | 3     | ❌       | ❌     | token_program: Used to burn the vending machine tokens                                               |
+    /// | 3     | ✅       | ❌     | vending_machine_mint: This is the token mint that is accepted by the `vending_machine_buffer`        |
+    /// | 4     | ❌       | ❌     | token_program: Used to burn the vending machine tokens                                               |
     VendingMachineEcho { data: Vec<u8> },
 }
+
+pub fn echo(program_id: &Pubkey, echo_buffer: &Pubkey, data: Vec<u8>) -> Instruction {
+    Instruction::new_with_borsh(
+        *program_id,
+        &EchoInstruction::Echo { data },
+        vec![AccountMeta::new(*echo_buffer, false)],
+    )
+}
+
+/// `authority` pays for the new buffer, so it is passed as writable.
+pub fn initialize_authorized_echo(
+    program_id: &Pubkey,
+    authority: &Pubkey,
+    buffer_seed: u64,
+    buffer_size: usize,
+) -> Instruction {
+    let (authorized_buffer, _) = find_authorized_buffer_address(program_id, authority, buffer_seed);
+    Instruction::new_with_borsh(
+        *program_id,
+        &EchoInstruction::InitializeAuthorizedEcho { buffer_seed, buffer_size },
+        vec![
+            AccountMeta::new(authorized_buffer, false),
+            AccountMeta::new(*authority, true),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+    )
+}
+
+pub fn authorized_echo(program_id: &Pubkey, authority: &Pubkey, buffer_seed: u64, data: Vec<u8>) -> Instruction {
+    let (authorized_buffer, _) = find_authorized_buffer_address(program_id, authority, buffer_seed);
+    Instruction::new_with_borsh(
+        *program_id,
+        &EchoInstruction::AuthorizedEcho { data },
+        vec![
+            AccountMeta::new(authorized_buffer, false),
+            AccountMeta::new_readonly(*authority, true),
+        ],
+    )
+}
+
+/// `payer` pays for the new buffer, so it is passed as writable.
+pub fn initialize_vending_machine_echo(
+    program_id: &Pubkey,
+    vending_machine_mint: &Pubkey,
+    payer: &Pubkey,
+    price: u64,
+    buffer_size: usize,
+) -> Instruction {
+    let (vending_machine_buffer, _) = find_vending_machine_buffer_address(program_id, vending_machine_mint, price);
+    Instruction::new_with_borsh(
+        *program_id,
+        &EchoInstruction::InitializeVendingMachineEcho { price, buffer_size },
+        vec![
+            AccountMeta::new(vending_machine_buffer, false),
+            AccountMeta::new_readonly(*vending_machine_mint, false),
+            AccountMeta::new(*payer, true),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+    )
+}
+
+/// The burn changes the mint's supply, so `vending_machine_mint` is passed as writable.
+pub fn vending_machine_echo(
+    program_id: &Pubkey,
+    user: &Pubkey,
+    user_token_account: &Pubkey,
+    vending_machine_mint: &Pubkey,
+    price: u64,
+    data: Vec<u8>,
+) -> Instruction {
+    let (vending_machine_buffer, _) = find_vending_machine_buffer_address(program_id, vending_machine_mint, price);
+    Instruction::new_with_borsh(
+        *program_id,
+        &EchoInstruction::VendingMachineEcho { data },
+        vec![
+            AccountMeta::new(vending_machine_buffer, false),
+            AccountMeta::new_readonly(*user, true),
+            AccountMeta::new(*user_token_account, false),
+            AccountMeta::new(*vending_machine_mint, false),
+            AccountMeta::new_readonly(spl_token::id(), false),
+        ],
+    )
+}
@@ -0,0 +1,19 @@
+//! Program addresses used by the echo program.
+
+use solana_program::pubkey::Pubkey;
+
+/// Address and bump seed of the buffer only `authority` can write to, for `buffer_seed`.
+pub fn find_authorized_buffer_address(program_id: &Pubkey, authority: &Pubkey, buffer_seed: u64) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[b"authority", authority.as_ref(), &buffer_seed.to_le_bytes()],
+        program_id,
+    )
+}
+
+/// Address and bump seed of the buffer anyone can write to by burning `price` tokens of `mint`.
+pub fn find_vending_machine_buffer_address(program_id: &Pubkey, mint: &Pubkey, price: u64) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[b"vending_machine", mint.as_ref(), &price.to_le_bytes()],
+        program_id,
+    )
+}
@@ -9,20 +9,18 @@ use solana_client::rpc_client::RpcClient;
 use solana_sdk::message::Message;
 use solana_sdk::program_error::ProgramError;
 use solana_sdk::account::ReadableAccount;
-use solana_sdk::instruction::AccountMeta;
-use solana_sdk::instruction::Instruction;
 use solana_sdk::program_pack::Pack;
 use solana_sdk::pubkey::Pubkey;
 use solana_sdk::rent::Rent;
 use solana_sdk::signature::Keypair;
 use solana_sdk::system_instruction;
-use solana_sdk::system_program;
 use solana_sdk::sysvar;
 use solana_sdk::{signature::Signer, transaction::Transaction};
 use solana_validator::test_validator::*;
 use spl_token::instruction::initialize_mint;
 
-use echo::instruction::EchoInstruction;
+use echo::instruction;
+use echo::pda::{find_authorized_buffer_address, find_vending_machine_buffer_address};
 
 #[test]
 fn test_echo() -> anyhow::Result<()> {
@@ -51,11 +49,7 @@ fn test_echo() -> anyhow::Result<()> {
                 data.len() as u64,
                 &program_id,
             ),
-            Instruction {
-                program_id,
-                accounts: vec![AccountMeta::new(echo_buffer.pubkey(), false)],
-                data: EchoInstruction::Echo { data }.try_to_vec()?,
-            },
+            instruction::echo(&program_id, &echo_buffer.pubkey(), data),
         ],
         Some(&payer.pubkey()),
         &vec![&payer, &echo_buffer],
@@ -100,16 +94,8 @@ fn test_echo_nonzero() -> anyhow::Result<()> {
                 data.len() as u64,
                 &program_id,
             ),
-            Instruction {
-                program_id,
-                accounts: vec![AccountMeta::new(echo_buffer.pubkey(), false)],
-                data: EchoInstruction::Echo { data }.try_to_vec().unwrap(),
-            },
-            Instruction {
-                program_id,
-                accounts: vec![AccountMeta::new(echo_buffer.pubkey(), false)],
-                data: EchoInstruction::Echo { data: data2 }.try_to_vec().unwrap(),
-            },
+            instruction::echo(&program_id, &echo_buffer.pubkey(), data),
+            instruction::echo(&program_id, &echo_buffer.pubkey(), data2),
         ],
         Some(&payer.pubkey()),
         &vec![&payer, &echo_buffer],
@@ -136,32 +122,13 @@ fn test_authorized_echo() -> anyhow::Result<()> {
     let rpc_client = test_validator.get_rpc_client();
 
     let buffer_seed = 1u64;
-    let (pda, _) = Pubkey::find_program_address(
-        &[
-            b"authority",
-            payer.pubkey().as_ref(),
-            &buffer_seed.to_le_bytes(),
-        ],
-        &program_id,
-    );
+    let (pda, _) = find_authorized_buffer_address(&program_id, &payer.pubkey(), buffer_seed);
 
     let data = b"authorized".to_vec();
 
     let blockhash = rpc_client.get_latest_blockhash()?;
     let mut transaction = Transaction::new_signed_with_payer(
-        &[Instruction {
-            program_id,
-            accounts: vec![
-                AccountMeta::new(pda, false),
-                AccountMeta::new(payer.pubkey(), true),
-                AccountMeta::new_readonly(system_program::id(), false),
-            ],
-            data: EchoInstruction::InitializeAuthorizedEcho {
-                buffer_seed,
-                buffer_size: 19,
-            }
-            .try_to_vec()?,
-        }],
+        &[instruction::initialize_authorized_echo(&program_id, &payer.pubkey(), buffer_seed, 19)],
         Some(&payer.pubkey()),
         &vec![&payer],
         blockhash,
@@ -173,14 +140,7 @@ fn test_authorized_echo() -> anyhow::Result<()> {
 
     let blockhash = rpc_client.get_latest_blockhash()?;
     let mut transaction = Transaction::new_signed_with_payer(
-        &[Instruction {
-            program_id,
-            accounts: vec![
-                AccountMeta::new(pda, false),
-                AccountMeta::new_readonly(payer.pubkey(), true),
-            ],
-            data: EchoInstruction::AuthorizedEcho { data }.try_to_vec()?,
-        }],
+        &[instruction::authorized_echo(&program_id, &payer.pubkey(), buffer_seed, data)],
         Some(&payer.pubkey()),
         &vec![&payer],
         blockhash,
@@ -206,14 +166,7 @@ fn test_vending_machine() -> anyhow::Result<()> {
     let rpc_client = test_validator.get_rpc_client();
 
     let price = 42u64;
-    let (pda, _) = Pubkey::find_program_address(
-        &[
-            b"vending_machine",
-            vending_machine_mint.pubkey().as_ref(),
-            &price.to_le_bytes(),
-        ],
-        &program_id,
-    );
+    let (pda, _) = find_vending_machine_buffer_address(&program_id, &vending_machine_mint.pubkey(), price);
 
     let blockhash = rpc_client.get_latest_blockhash()?;
     let mut transaction = Transaction::new_signed_with_payer(
@@ -254,20 +207,13 @@ fn test_vending_machine() -> anyhow::Result<()> {
                 &[&payer.pubkey()],
                 42,
             )?,
-            Instruction {
-                program_id,
-                accounts: vec![
-                    AccountMeta::new(pda, false),
-                    AccountMeta::new_readonly(vending_machine_mint.pubkey(), false),
-                    AccountMeta::new(payer.pubkey(), true),
-                    AccountMeta::new_readonly(system_program::id(), false),
-                ],
-                data: EchoInstruction::InitializeVendingMachineEcho {
-                    price,
-                    buffer_size: 24,
-                }
-                .try_to_vec()?,
-            },
+            instruction::initialize_vending_machine_echo(
+                &program_id,
+                &vending_machine_mint.pubkey(),
+                &payer.pubkey(),
+                price,
+                24,
+            ),
         ],
         Some(&payer.pubkey()),
         &vec![&payer, &vending_machine_mint, &user_token_account],
@@ -289,20 +235,14 @@ fn test_vending_machine() -> anyhow::Result<()> {
 
     let blockhash = rpc_client.get_latest_blockhash()?;
     let mut transaction = Transaction::new_signed_with_payer(
-        &[Instruction {
-            program_id,
-            accounts: vec![
-                AccountMeta::new(pda, false),
-                AccountMeta::new_readonly(payer.pubkey(), true),
-                AccountMeta::new(user_token_account.pubkey(), false),
-                AccountMeta::new(vending_machine_mint.pubkey(), false),
-                AccountMeta::new_readonly(spl_token::id(), false),
-            ],
-            data: EchoInstruction::VendingMachineEcho {
-                data: b"vending machine".to_vec(),
-            }
-            .try_to_vec()?,
-        }],
+        &[instruction::vending_machine_echo(
+            &program_id,
+            &payer.pubkey(),
+            &user_token_account.pubkey(),
+            &vending_machine_mint.pubkey(),
+            price,
+            b"vending machine".to_vec(),
+        )],
         Some(&payer.pubkey()),
         &vec![&payer],
         blockhash,
@@ -0,0 +1,729 @@
+use anyhow::{anyhow, bail, Context};
+use borsh::{BorshDeserialize, BorshSerialize};
+use clap::{crate_description, crate_name, crate_version, App, AppSettings, Arg, ArgMatches, SubCommand};
+use solana_clap_utils::input_parsers::{pubkey_of, value_of};
+use solana_clap_utils::input_validators::{is_parsable, is_url_or_moniker, is_valid_pubkey, normalize_to_url_if_moniker};
+use solana_client::rpc_client::RpcClient;
+use solana_sdk::commitment_config::CommitmentConfig;
+use solana_sdk::instruction::Instruction;
+use solana_sdk::program_pack::Pack;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::{read_keypair_file, Keypair, Signer};
+use solana_sdk::transaction::Transaction;
+use spl_associated_token_account::get_associated_token_address;
+use spl_token::state::{Account, Mint};
+
+use echo::pda::find_authorized_buffer_address;
+
+use exchange_booth::instruction::{self, Direction, QuoteResult};
+use exchange_booth::pda::{find_exchange_booth_address, find_vault_address};
+use exchange_booth::state::{ExchangeBooth, Oracle, VersionedExchangeBooth};
+
+struct Config {
+    rpc_client: RpcClient,
+    payer: Keypair,
+    program_id: Pubkey,
+}
+
+/// A booth read back from the chain, with the mints of its two vaults.
+struct Booth {
+    address: Pubkey,
+    state: ExchangeBooth,
+    mint_a: Pubkey,
+    mint_b: Pubkey,
+}
+
+impl Booth {
+    fn load(config: &Config, address: Pubkey) -> anyhow::Result<Booth> {
+        let account = config
+            .rpc_client
+            .get_account(&address)
+            .with_context(|| format!("no exchange booth at {}", address))?;
+        if account.owner != config.program_id {
+            bail!("{} is not an exchange booth", address);
+        }
+        let state = match VersionedExchangeBooth::unpack(&account.data)? {
+            VersionedExchangeBooth::V1(_) => bail!("{} uses the legacy layout and must be migrated first", address),
+            VersionedExchangeBooth::V2(state) => *state,
+        };
+        let mint_a = token_account(config, &state.vault_a)?.mint;
+        let mint_b = token_account(config, &state.vault_b)?.mint;
+        Ok(Booth { address, state, mint_a, mint_b })
+    }
+
+    /// Direction of a trade that deposits `mint`, and the mint it pays out.
+    fn direction(&self, mint: &Pubkey) -> anyhow::Result<(Direction, Pubkey)> {
+        if *mint == self.mint_a {
+            Ok((Direction::AToB, self.mint_b))
+        } else if *mint == self.mint_b {
+            Ok((Direction::BToA, self.mint_a))
+        } else {
+            bail!("{} is not one of the booth's mints", mint)
+        }
+    }
+}
+
+fn token_account(config: &Config, address: &Pubkey) -> anyhow::Result<Account> {
+    let data = config.rpc_client.get_account_data(address)?;
+    Account::unpack(&data).with_context(|| format!("{} is not a token account", address))
+}
+
+fn mint_decimals(config: &Config, mint: &Pubkey) -> anyhow::Result<u8> {
+    let data = config.rpc_client.get_account_data(mint)?;
+    Ok(Mint::unpack(&data).with_context(|| format!("{} is not a mint", mint))?.decimals)
+}
+
+/// The token account named by `name`, or the payer's associated token account for `mint`.
+fn token_account_or_default(config: &Config, matches: &ArgMatches, name: &str, mint: &Pubkey) -> Pubkey {
+    pubkey_of(matches, name).unwrap_or_else(|| get_associated_token_address(&config.payer.pubkey(), mint))
+}
+
+fn send(config: &Config, instructions: &[Instruction]) -> anyhow::Result<()> {
+    let blockhash = config.rpc_client.get_latest_blockhash()?;
+    let transaction = Transaction::new_signed_with_payer(
+        instructions,
+        Some(&config.payer.pubkey()),
+        &[&config.payer],
+        blockhash,
+    );
+    let signature = config.rpc_client.send_and_confirm_transaction(&transaction)?;
+    println!("Signature: {}", signature);
+    Ok(())
+}
+
+fn command_init(config: &Config, matches: &ArgMatches) -> anyhow::Result<()> {
+    let admin = config.payer.pubkey();
+    let mint_a = pubkey_of(matches, "mint_a").unwrap();
+    let mint_b = pubkey_of(matches, "mint_b").unwrap();
+    let (exchange_booth, _) = find_exchange_booth_address(&config.program_id, &admin, &mint_a, &mint_b);
+
+    send(config, &[instruction::initialize_exchange_booth(
+        &config.program_id,
+        &admin,
+        &mint_a,
+        &mint_b,
+        &pubkey_of(matches, "oracle").unwrap(),
+        value_of(matches, "fee_bps").unwrap(),
+        value_of(matches, "max_staleness_slots").unwrap(),
+        &pubkey_of(matches, "oracle_program_id").unwrap(),
+        &pubkey_of(matches, "oracle_authority").unwrap_or(admin),
+    )])?;
+    println!("Exchange booth: {}", exchange_booth);
+    Ok(())
+}
+
+fn command_deposit(config: &Config, matches: &ArgMatches) -> anyhow::Result<()> {
+    let booth = Booth::load(config, pubkey_of(matches, "exchange_booth").unwrap())?;
+    let mint = pubkey_of(matches, "mint").unwrap();
+    booth.direction(&mint)?;
+    let src = token_account_or_default(config, matches, "from", &mint);
+
+    send(config, &[instruction::deposit(
+        &config.program_id,
+        &booth.address,
+        &mint,
+        &src,
+        &config.payer.pubkey(),
+        value_of(matches, "amount").unwrap(),
+    )])
+}
+
+fn command_withdraw(config: &Config, matches: &ArgMatches) -> anyhow::Result<()> {
+    let booth = Booth::load(config, pubkey_of(matches, "exchange_booth").unwrap())?;
+    let mint = pubkey_of(matches, "mint").unwrap();
+    booth.direction(&mint)?;
+    let dst = token_account_or_default(config, matches, "to", &mint);
+
+    send(config, &[instruction::withdraw(
+        &config.program_id,
+        &booth.address,
+        &mint,
+        &dst,
+        &config.payer.pubkey(),
+        value_of(matches, "amount").unwrap(),
+    )])
+}
+
+fn command_exchange(config: &Config, matches: &ArgMatches) -> anyhow::Result<()> {
+    let booth = Booth::load(config, pubkey_of(matches, "exchange_booth").unwrap())?;
+    let deposit_mint = pubkey_of(matches, "mint").unwrap();
+    let (_, withdraw_mint) = booth.direction(&deposit_mint)?;
+    let src = token_account_or_default(config, matches, "from", &deposit_mint);
+    let dst = token_account_or_default(config, matches, "to", &withdraw_mint);
+
+    send(config, &[instruction::exchange(
+        &config.program_id,
+        &booth.address,
+        &booth.state.oracle,
+        &deposit_mint,
+        &withdraw_mint,
+        &src,
+        &dst,
+        &config.payer.pubkey(),
+        value_of(matches, "amount").unwrap(),
+        value_of(matches, "min_amount_out").unwrap(),
+    )])
+}
+
+/// Prices an exchange of `amount` in `direction` without making it.
+fn fetch_quote(config: &Config, booth: &Booth, direction: Direction, amount: u64) -> anyhow::Result<QuoteResult> {
+    // Quote only returns data, so simulating it is enough
+    let blockhash = config.rpc_client.get_latest_blockhash()?;
+    let transaction = Transaction::new_signed_with_payer(
+        &[instruction::quote(
+            &config.program_id,
+            &booth.address,
+            &booth.state.oracle,
+            &booth.mint_a,
+            &booth.mint_b,
+            amount,
+            direction,
+        )],
+        Some(&config.payer.pubkey()),
+        &[&config.payer],
+        blockhash,
+    );
+    let result = config.rpc_client.simulate_transaction(&transaction)?.value;
+    if let Some(err) = result.err {
+        bail!("quote failed: {:?}", err);
+    }
+    QuoteResult::from_logs(&config.program_id, &result.logs.unwrap_or_default())
+        .ok_or_else(|| anyhow!("no quote returned"))
+}
+
+fn command_quote(config: &Config, matches: &ArgMatches) -> anyhow::Result<()> {
+    let booth = Booth::load(config, pubkey_of(matches, "exchange_booth").unwrap())?;
+    let deposit_mint = pubkey_of(matches, "mint").unwrap();
+    let (direction, withdraw_mint) = booth.direction(&deposit_mint)?;
+    let amount = value_of(matches, "amount").unwrap();
+    let quote = fetch_quote(config, &booth, direction, amount)?;
+
+    let deposit_decimals = mint_decimals(config, &deposit_mint)?;
+    let withdraw_decimals = mint_decimals(config, &withdraw_mint)?;
+    println!("In:          {} {}", ui_amount(amount, deposit_decimals), deposit_mint);
+    println!("Out:         {} {}", ui_amount(quote.amount_out, withdraw_decimals), withdraw_mint);
+    println!("Fee:         {} {}", ui_amount(quote.fee, withdraw_decimals), withdraw_mint);
+    println!("Oracle rate: {}e{} (slot {})", quote.mantissa, quote.exponent, quote.last_update_slot);
+    Ok(())
+}
+
+fn command_close(config: &Config, matches: &ArgMatches) -> anyhow::Result<()> {
+    let booth = Booth::load(config, pubkey_of(matches, "exchange_booth").unwrap())?;
+    let dst_a = token_account_or_default(config, matches, "to_a", &booth.mint_a);
+    let dst_b = token_account_or_default(config, matches, "to_b", &booth.mint_b);
+
+    send(config, &[instruction::close_exchange_booth(
+        &config.program_id,
+        &booth.address,
+        &config.payer.pubkey(),
+        &booth.mint_a,
+        &booth.mint_b,
+        &dst_a,
+        &dst_b,
+    )])
+}
+
+fn command_show(config: &Config, matches: &ArgMatches) -> anyhow::Result<()> {
+    let booth = Booth::load(config, pubkey_of(matches, "exchange_booth").unwrap())?;
+    let state = &booth.state;
+    let decimals_a = mint_decimals(config, &booth.mint_a)?;
+    let decimals_b = mint_decimals(config, &booth.mint_b)?;
+    let balance_a = token_account(config, &state.vault_a)?.amount;
+    let balance_b = token_account(config, &state.vault_b)?.amount;
+    let pending_admin = if state.pending_admin == Pubkey::default() {
+        "none".to_string()
+    } else {
+        state.pending_admin.to_string()
+    };
+
+    println!("Exchange booth:    {}", booth.address);
+    println!("Version:           {}", state.version);
+    println!("Admin:             {}", state.admin);
+    println!("Pending admin:     {}", pending_admin);
+    println!("Paused:            {}", state.is_paused());
+    println!("Public liquidity:  {}", state.is_public_liquidity());
+    println!("Fee:               {} bps", state.fee_bps);
+    println!();
+    println!("Oracle:            {}", state.oracle);
+    match read_oracle(config, &state.oracle) {
+        Some(oracle) => println!("Oracle rate:       {}e{} (slot {})", oracle.mantissa, oracle.exponent, oracle.last_update_slot),
+        None => println!("Oracle rate:       unreadable"),
+    }
+    println!("Oracle program:    {}", state.oracle_program_id);
+    println!("Oracle authority:  {}", state.oracle_authority);
+    println!("Max staleness:     {} slots", state.max_staleness_slots);
+    println!();
+    println!("Mint A:            {}", booth.mint_a);
+    println!("Vault A:           {}", state.vault_a);
+    println!("Balance A:         {}", ui_amount(balance_a, decimals_a));
+    println!("Mint B:            {}", booth.mint_b);
+    println!("Vault B:           {}", state.vault_b);
+    println!("Balance B:         {}", ui_amount(balance_b, decimals_b));
+    println!();
+    println!("Deposited:         {} A, {} B", ui_amount(state.total_deposited_a, decimals_a), ui_amount(state.total_deposited_b, decimals_b));
+    println!("Withdrawn:         {} A, {} B", ui_amount(state.total_withdrawn_a, decimals_a), ui_amount(state.total_withdrawn_b, decimals_b));
+    println!("Volume:            {} A -> B, {} B -> A", ui_amount(state.volume_a_to_b, decimals_a), ui_amount(state.volume_b_to_a, decimals_b));
+    println!("Fees collected:    {} A, {} B", ui_amount(state.fees_collected_a, decimals_a), ui_amount(state.fees_collected_b, decimals_b));
+    println!("Trades:            {}", state.trade_count);
+
+    // the vault addresses are stored, but rederiving them catches a booth that points elsewhere
+    for (vault, mint) in [(state.vault_a, booth.mint_a), (state.vault_b, booth.mint_b)] {
+        if find_vault_address(&config.program_id, &booth.address, &mint).0 != vault {
+            println!("Warning: {} is not the booth's program address for {}", vault, mint);
+        }
+    }
+    Ok(())
+}
+
+/// Publishes `mantissa * 10^exponent` at the current slot to an echo buffer owned by the keypair,
+/// creating the buffer first if needed. Booths refuse stale rates, so rerun it to keep one fresh.
+fn command_oracle(config: &Config, matches: &ArgMatches) -> anyhow::Result<()> {
+    let echo_program_id = pubkey_of(matches, "echo_program_id").unwrap();
+    let buffer_seed = value_of(matches, "seed").unwrap();
+    let authority = config.payer.pubkey();
+    let (oracle, _) = find_authorized_buffer_address(&echo_program_id, &authority, buffer_seed);
+
+    let mut instructions = Vec::new();
+    if config.rpc_client.get_balance(&oracle)? == 0 {
+        instructions.push(echo::instruction::initialize_authorized_echo(
+            &echo_program_id,
+            &authority,
+            buffer_seed,
+            Oracle::HEADER_LEN + Oracle::LEN,
+        ));
+    }
+    let oracle_data = Oracle {
+        mantissa: value_of(matches, "mantissa").unwrap(),
+        exponent: value_of(matches, "exponent").unwrap(),
+        last_update_slot: config.rpc_client.get_slot()?,
+    };
+    instructions.push(echo::instruction::authorized_echo(
+        &echo_program_id,
+        &authority,
+        buffer_seed,
+        oracle_data.try_to_vec()?,
+    ));
+
+    send(config, &instructions)?;
+    println!("Oracle: {}", oracle);
+    Ok(())
+}
+
+/// The rate currently in an oracle buffer, if it holds one.
+fn read_oracle(config: &Config, oracle: &Pubkey) -> Option<Oracle> {
+    let data = config.rpc_client.get_account_data(oracle).ok()?;
+    Oracle::try_from_slice(data.get(Oracle::HEADER_LEN..Oracle::HEADER_LEN + Oracle::LEN)?).ok()
+}
+
+/// `amount` base units as a decimal number of whole tokens, without trailing zeros.
+fn ui_amount(amount: u64, decimals: u8) -> String {
+    let decimals = decimals as usize;
+    let digits = format!("{:0>width$}", amount, width = decimals + 1);
+    let (whole, fraction) = digits.split_at(digits.len() - decimals);
+    match fraction.trim_end_matches('0') {
+        "" => whole.to_string(),
+        fraction => format!("{}.{}", whole, fraction),
+    }
+}
+
+fn exchange_booth_arg<'a, 'b>() -> Arg<'a, 'b> {
+    Arg::with_name("exchange_booth")
+        .value_name("EXCHANGE_BOOTH")
+        .validator(is_valid_pubkey)
+        .required(true)
+        .help("Address of the exchange booth")
+}
+
+fn mint_arg<'a, 'b>(help: &'b str) -> Arg<'a, 'b> {
+    Arg::with_name("mint")
+        .value_name("MINT")
+        .validator(is_valid_pubkey)
+        .required(true)
+        .help(help)
+}
+
+fn amount_arg<'a, 'b>() -> Arg<'a, 'b> {
+    Arg::with_name("amount")
+        .value_name("AMOUNT")
+        .validator(is_parsable::<u64>)
+        .required(true)
+        .help("Amount in base units")
+}
+
+fn token_account_arg<'a, 'b>(name: &'a str, help: &'b str) -> Arg<'a, 'b> {
+    Arg::with_name(name)
+        .long(name)
+        .value_name("TOKEN_ACCOUNT")
+        .validator(is_valid_pubkey)
+        .takes_value(true)
+        .help(help)
+}
+
+fn app<'a, 'b>() -> App<'a, 'b> {
+    App::new(crate_name!())
+        .about(crate_description!())
+        .version(crate_version!())
+        .setting(AppSettings::SubcommandRequiredElseHelp)
+        .arg(
+            Arg::with_name("config_file")
+                .short("C")
+                .long("config")
+                .value_name("PATH")
+                .takes_value(true)
+                .global(true)
+                .help("Solana CLI config file to read the defaults below from"),
+        )
+        .arg(
+            Arg::with_name("json_rpc_url")
+                .short("u")
+                .long("url")
+                .value_name("URL_OR_MONIKER")
+                .takes_value(true)
+                .global(true)
+                .validator(is_url_or_moniker)
+                .help("JSON RPC URL of the cluster, or a moniker like localhost [default: from the config file]"),
+        )
+        .arg(
+            Arg::with_name("keypair")
+                .short("k")
+                .long("keypair")
+                .value_name("KEYPAIR")
+                .takes_value(true)
+                .global(true)
+                .help("Keypair that pays and signs, and is the admin for admin commands [default: from the config file]"),
+        )
+        .arg(
+            Arg::with_name("program_id")
+                .short("p")
+                .long("program-id")
+                .value_name("PROGRAM_ID")
+                .takes_value(true)
+                .global(true)
+                .validator(is_valid_pubkey)
+                .help("Address the exchange booth program is deployed at [required]"),
+        )
+        .subcommand(
+            SubCommand::with_name("init")
+                .about("Create an exchange booth for two mints, administered by the keypair")
+                .arg(
+                    Arg::with_name("mint_a")
+                        .value_name("MINT_A")
+                        .validator(is_valid_pubkey)
+                        .required(true)
+                        .help("Mint the oracle rate is quoted per"),
+                )
+                .arg(
+                    Arg::with_name("mint_b")
+                        .value_name("MINT_B")
+                        .validator(is_valid_pubkey)
+                        .required(true)
+                        .help("Mint the oracle rate is quoted in"),
+                )
+                .arg(
+                    Arg::with_name("oracle")
+                        .long("oracle")
+                        .value_name("ORACLE")
+                        .validator(is_valid_pubkey)
+                        .required(true)
+                        .help("Echo buffer quoting mint_b per mint_a"),
+                )
+                .arg(
+                    Arg::with_name("oracle_program_id")
+                        .long("oracle-program-id")
+                        .value_name("PROGRAM_ID")
+                        .validator(is_valid_pubkey)
+                        .required(true)
+                        .help("Echo program that owns the oracle"),
+                )
+                .arg(
+                    Arg::with_name("oracle_authority")
+                        .long("oracle-authority")
+                        .value_name("PUBKEY")
+                        .validator(is_valid_pubkey)
+                        .help("Authority of the oracle buffer [default: the keypair]"),
+                )
+                .arg(
+                    Arg::with_name("fee_bps")
+                        .long("fee-bps")
+                        .value_name("BPS")
+                        .validator(is_parsable::<u16>)
+                        .default_value("0")
+                        .help("Fee taken out of every exchange, in basis points"),
+                )
+                .arg(
+                    Arg::with_name("max_staleness_slots")
+                        .long("max-staleness-slots")
+                        .value_name("SLOTS")
+                        .validator(is_parsable::<u64>)
+                        .default_value("100")
+                        .help("How old the oracle may be before exchanges are refused"),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("deposit")
+                .about("Deposit tokens into one of the booth's vaults")
+                .arg(exchange_booth_arg())
+                .arg(mint_arg("Mint of the vault to deposit into"))
+                .arg(amount_arg())
+                .arg(token_account_arg("from", "Token account to deposit from [default: associated token account]")),
+        )
+        .subcommand(
+            SubCommand::with_name("withdraw")
+                .about("Withdraw tokens from one of the booth's vaults")
+                .arg(exchange_booth_arg())
+                .arg(mint_arg("Mint of the vault to withdraw from"))
+                .arg(amount_arg())
+                .arg(token_account_arg("to", "Token account to withdraw to [default: associated token account]")),
+        )
+        .subcommand(
+            SubCommand::with_name("exchange")
+                .about("Exchange tokens of one mint for the other at the oracle rate")
+                .arg(exchange_booth_arg())
+                .arg(mint_arg("Mint to pay in"))
+                .arg(amount_arg())
+                .arg(
+                    Arg::with_name("min_amount_out")
+                        .long("min-amount-out")
+                        .value_name("AMOUNT")
+                        .validator(is_parsable::<u64>)
+                        .default_value("0")
+                        .help("Fail unless at least this much is paid out, in base units"),
+                )
+                .arg(token_account_arg("from", "Token account to pay from [default: associated token account]"))
+                .arg(token_account_arg("to", "Token account to be paid into [default: associated token account]")),
+        )
+        .subcommand(
+            SubCommand::with_name("quote")
+                .about("Price an exchange without making it")
+                .arg(exchange_booth_arg())
+                .arg(mint_arg("Mint to pay in"))
+                .arg(amount_arg()),
+        )
+        .subcommand(
+            SubCommand::with_name("close")
+                .about("Pay out both vaults and close the booth")
+                .arg(exchange_booth_arg())
+                .arg(token_account_arg("to_a", "Token account for the mint_a vault [default: associated token account]").long("to-a"))
+                .arg(token_account_arg("to_b", "Token account for the mint_b vault [default: associated token account]").long("to-b")),
+        )
+        .subcommand(
+            SubCommand::with_name("oracle")
+                .about("Publish a rate to an echo buffer owned by the keypair, for testing")
+                .arg(
+                    Arg::with_name("echo_program_id")
+                        .value_name("ECHO_PROGRAM_ID")
+                        .validator(is_valid_pubkey)
+                        .required(true)
+                        .help("Address the echo program is deployed at"),
+                )
+                .arg(
+                    Arg::with_name("mantissa")
+                        .long("mantissa")
+                        .value_name("MANTISSA")
+                        .validator(is_parsable::<u64>)
+                        .required(true)
+                        .help("Rate is MANTISSA * 10^EXPONENT whole mint_b per whole mint_a"),
+                )
+                .arg(
+                    Arg::with_name("exponent")
+                        .long("exponent")
+                        .value_name("EXPONENT")
+                        .validator(is_parsable::<i32>)
+                        .allow_hyphen_values(true)
+                        .default_value("0"),
+                )
+                .arg(
+                    Arg::with_name("seed")
+                        .long("seed")
+                        .value_name("SEED")
+                        .validator(is_parsable::<u64>)
+                        .default_value("0")
+                        .help("Buffer seed, to keep several oracles under one keypair"),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("show")
+                .about("Show a booth's settings, vault balances and running totals")
+                .arg(exchange_booth_arg()),
+        )
+}
+
+fn main() -> anyhow::Result<()> {
+    let matches = app().get_matches();
+
+    let cli_config = match matches.value_of("config_file").or(solana_cli_config::CONFIG_FILE.as_deref()) {
+        Some(config_file) => solana_cli_config::Config::load(config_file).unwrap_or_default(),
+        None => solana_cli_config::Config::default(),
+    };
+    // clap 2 cannot require a global argument, so check for it here
+    let program_id = pubkey_of(&matches, "program_id").ok_or_else(|| anyhow!("--program-id is required"))?;
+    let json_rpc_url = normalize_to_url_if_moniker(matches.value_of("json_rpc_url").unwrap_or(&cli_config.json_rpc_url));
+    let keypair_path = matches.value_of("keypair").unwrap_or(&cli_config.keypair_path);
+    let payer = read_keypair_file(keypair_path)
+        .map_err(|err| anyhow!("failed to read keypair {}: {}", keypair_path, err))?;
+
+    let config = Config {
+        rpc_client: RpcClient::new_with_commitment(json_rpc_url, CommitmentConfig::confirmed()),
+        payer,
+        program_id,
+    };
+    process(&config, &matches)
+}
+
+/// Runs the subcommand in `matches`.
+fn process(config: &Config, matches: &ArgMatches) -> anyhow::Result<()> {
+    match matches.subcommand() {
+        ("init", Some(matches)) => command_init(config, matches),
+        ("deposit", Some(matches)) => command_deposit(config, matches),
+        ("withdraw", Some(matches)) => command_withdraw(config, matches),
+        ("exchange", Some(matches)) => command_exchange(config, matches),
+        ("quote", Some(matches)) => command_quote(config, matches),
+        ("close", Some(matches)) => command_close(config, matches),
+        ("oracle", Some(matches)) => command_oracle(config, matches),
+        ("show", Some(matches)) => command_show(config, matches),
+        _ => unreachable!(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ui_amount() {
+        assert_eq!(ui_amount(0, 0), "0");
+        assert_eq!(ui_amount(42, 0), "42");
+        assert_eq!(ui_amount(0, 6), "0");
+        assert_eq!(ui_amount(1_500_000, 6), "1.5");
+        assert_eq!(ui_amount(2_000_000, 6), "2");
+        assert_eq!(ui_amount(1_000_001, 6), "1.000001");
+        // fewer digits than decimals
+        assert_eq!(ui_amount(5, 6), "0.000005");
+        assert_eq!(ui_amount(120, 6), "0.00012");
+        assert_eq!(ui_amount(u64::MAX, 9), "18446744073.709551615");
+    }
+}
+
+#[cfg(all(test, feature = "test-bpf"))]
+mod test_validator {
+    use exchange_booth::error::ExchangeBoothError;
+    use solana_client::client_error::ClientError;
+    use solana_sdk::instruction::InstructionError;
+    use solana_sdk::system_instruction;
+    use solana_sdk::transaction::TransactionError;
+    use solana_validator::test_validator::TestValidatorGenesis;
+    use spl_associated_token_account::create_associated_token_account;
+
+    use super::*;
+
+    fn run(config: &Config, args: &[&str]) -> anyhow::Result<()> {
+        let matches = app().get_matches_from(std::iter::once("exchange-booth-cli").chain(args.iter().copied()));
+        process(config, &matches)
+    }
+
+    fn balance(config: &Config, address: &Pubkey) -> anyhow::Result<u64> {
+        Ok(token_account(config, address)?.amount)
+    }
+
+    fn is_closed(config: &Config, address: &Pubkey) -> anyhow::Result<bool> {
+        Ok(config.rpc_client.get_account_with_commitment(address, config.rpc_client.commitment())?.value.is_none())
+    }
+
+    #[test]
+    fn test_commands() -> anyhow::Result<()> {
+        solana_logger::setup_with_default("solana_program_runtime=debug");
+        let exchange_booth_program_id = Pubkey::new_unique();
+        let echo_program_id = Pubkey::new_unique();
+        let mint_a = Keypair::new();
+        let mint_b = Keypair::new();
+
+        let (test_validator, payer) = TestValidatorGenesis::default()
+            .add_program("exchange_booth", exchange_booth_program_id)
+            .add_program("echo", echo_program_id)
+            .start();
+        let config = Config {
+            rpc_client: test_validator.get_rpc_client(),
+            payer,
+            program_id: exchange_booth_program_id,
+        };
+        let payer = config.payer.pubkey();
+        let wallet_a = get_associated_token_address(&payer, &mint_a.pubkey());
+        let wallet_b = get_associated_token_address(&payer, &mint_b.pubkey());
+
+        // two mints with 100 of each in the keypair's associated token accounts
+        let mut instructions = Vec::new();
+        for (mint, wallet) in [(&mint_a, &wallet_a), (&mint_b, &wallet_b)] {
+            instructions.extend([
+                system_instruction::create_account(
+                    &payer,
+                    &mint.pubkey(),
+                    config.rpc_client.get_minimum_balance_for_rent_exemption(Mint::LEN)?,
+                    Mint::LEN as u64,
+                    &spl_token::id(),
+                ),
+                spl_token::instruction::initialize_mint(&spl_token::id(), &mint.pubkey(), &payer, None, 0)?,
+                create_associated_token_account(&payer, &payer, &mint.pubkey()),
+                spl_token::instruction::mint_to(&spl_token::id(), &mint.pubkey(), wallet, &payer, &[], 100)?,
+            ]);
+        }
+        let blockhash = config.rpc_client.get_latest_blockhash()?;
+        let transaction = Transaction::new_signed_with_payer(
+            &instructions,
+            Some(&payer),
+            &[&config.payer, &mint_a, &mint_b],
+            blockhash,
+        );
+        config.rpc_client.send_and_confirm_transaction(&transaction)?;
+
+        let echo = echo_program_id.to_string();
+        let (oracle, _) = find_authorized_buffer_address(&echo_program_id, &payer, 0);
+        let (exchange_booth, _) = find_exchange_booth_address(&exchange_booth_program_id, &payer, &mint_a.pubkey(), &mint_b.pubkey());
+        let (oracle, exchange_booth) = (oracle.to_string(), exchange_booth.to_string());
+        let (mint_a, mint_b) = (mint_a.pubkey().to_string(), mint_b.pubkey().to_string());
+
+        run(&config, &["oracle", &echo, "--mantissa", "20", "--exponent", "-1"])?;
+        run(&config, &["init", &mint_a, &mint_b, "--oracle", &oracle, "--oracle-program-id", &echo, "--fee-bps", "100"])?;
+        let booth = Booth::load(&config, exchange_booth.parse()?)?;
+        assert_eq!(booth.state.admin, payer);
+        assert_eq!(booth.state.fee_bps, 100);
+        assert_eq!(booth.state.oracle.to_string(), oracle);
+        assert_eq!((booth.mint_a.to_string(), booth.mint_b.to_string()), (mint_a.clone(), mint_b.clone()));
+
+        run(&config, &["deposit", &exchange_booth, &mint_b, "50"])?;
+        assert_eq!(balance(&config, &booth.state.vault_b)?, 50);
+        assert_eq!(balance(&config, &wallet_b)?, 50);
+
+        // 10 A at 2.0 is 20 B, less a 1% fee rounded up
+        run(&config, &["quote", &exchange_booth, &mint_a, "10"])?;
+        let quote = fetch_quote(&config, &booth, Direction::AToB, 10)?;
+        assert_eq!((quote.amount_out, quote.fee), (19, 1));
+
+        let err = run(&config, &["exchange", &exchange_booth, &mint_a, "10", "--min-amount-out", "20"]).unwrap_err();
+        assert_eq!(
+            err.downcast_ref::<ClientError>().and_then(|err| err.get_transaction_error()),
+            Some(TransactionError::InstructionError(0, InstructionError::Custom(ExchangeBoothError::SlippageExceeded as u32))),
+            "{:?}",
+            err,
+        );
+        run(&config, &["exchange", &exchange_booth, &mint_a, "10", "--min-amount-out", "19"])?;
+        assert_eq!(balance(&config, &wallet_a)?, 90);
+        assert_eq!(balance(&config, &wallet_b)?, 69);
+
+        run(&config, &["show", &exchange_booth])?;
+        let err = run(&config, &["show", &mint_a]).unwrap_err();
+        assert_eq!(err.to_string(), format!("{} is not an exchange booth", mint_a));
+
+        run(&config, &["withdraw", &exchange_booth, &mint_b, "11"])?;
+        assert_eq!(balance(&config, &booth.state.vault_b)?, 20);
+        assert_eq!(balance(&config, &wallet_b)?, 80);
+
+        // closing pays out the 10 A exchanged in and the 20 B left over
+        run(&config, &["close", &exchange_booth])?;
+        assert_eq!(balance(&config, &wallet_a)?, 100);
+        assert_eq!(balance(&config, &wallet_b)?, 100);
+        assert!(is_closed(&config, &booth.state.vault_a)?);
+        assert!(is_closed(&config, &booth.state.vault_b)?);
+        assert!(is_closed(&config, &booth.address)?);
+        Ok(())
+    }
+}
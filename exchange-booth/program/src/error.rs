@@ -2,6 +2,8 @@ use num_derive::FromPrimitive;
 use solana_program::program_error::ProgramError;
 use thiserror::Error;
 
+use crate::pricing::PricingError;
+
 #[derive(Error, Debug, Copy, Clone, FromPrimitive, PartialEq)]
 pub enum ExchangeBoothError {
     #[error("Account must be writable.")]
@@ -28,6 +30,26 @@ pub enum ExchangeBoothError {
     UnknownOracle,
     #[error("Overflow.")]
     Overflow,
+    #[error("Exchange output is below the minimum amount out, or its input is above the maximum amount in.")]
+    SlippageExceeded,
+    #[error("Oracle price is stale.")]
+    StaleOracle,
+    #[error("Oracle header does not match the oracle authority's echo buffer.")]
+    InvalidOracleHeader,
+    #[error("Oracle data is too short.")]
+    OracleDataTooShort,
+    #[error("Signer is not the pending admin.")]
+    IncorrectPendingAdmin,
+    #[error("Exchange booth is paused.")]
+    ExchangeBoothPaused,
+    #[error("Exchange booth must be migrated to the latest layout.")]
+    OutdatedExchangeBooth,
+    #[error("Unsupported exchange booth version.")]
+    UnsupportedVersion,
+    #[error("Token account mint does not match the vault.")]
+    MintMismatch,
+    #[error("Vault balance is too low for the withdrawal.")]
+    InsufficientVaultBalance,
 }
 
 impl From<ExchangeBoothError> for ProgramError {
@@ -35,3 +57,19 @@ impl From<ExchangeBoothError> for ProgramError {
         ProgramError::Custom(e as u32)
     }
 }
+
+impl From<PricingError> for ExchangeBoothError {
+    fn from(e: PricingError) -> Self {
+        match e {
+            PricingError::Overflow => ExchangeBoothError::Overflow,
+            PricingError::ZeroRate => ExchangeBoothError::InvalidAccountData,
+            PricingError::InvalidFee => ExchangeBoothError::InvalidInstructionInput,
+        }
+    }
+}
+
+impl From<PricingError> for ProgramError {
+    fn from(e: PricingError) -> Self {
+        ExchangeBoothError::from(e).into()
+    }
+}
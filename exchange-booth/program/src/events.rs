@@ -0,0 +1,215 @@
+use borsh::{BorshDeserialize, BorshSerialize};
+use solana_program::entrypoint::ProgramResult;
+use solana_program::log::sol_log_data;
+use solana_program::pubkey::Pubkey;
+
+/// Every instruction other than `Quote` logs exactly one event through `sol_log_data`, which
+/// shows up in the transaction logs as `Program data: <base64>`. Use [`decode_events`] to read
+/// them back.
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, PartialEq)]
+pub enum ExchangeBoothEvent {
+    BoothInitialized {
+        exchange_booth: Pubkey,
+        admin: Pubkey,
+        vault_a: Pubkey,
+        vault_b: Pubkey,
+        oracle: Pubkey,
+        fee_bps: u16,
+    },
+    Deposited {
+        exchange_booth: Pubkey,
+        vault: Pubkey,
+        authority: Pubkey,
+        amount: u64,
+    },
+    Withdrew {
+        exchange_booth: Pubkey,
+        vault: Pubkey,
+        amount: u64,
+    },
+    Exchanged {
+        exchange_booth: Pubkey,
+        authority: Pubkey,
+        a_to_b: bool,
+        amount_in: u64,
+        amount_out: u64,
+        fee: u64,
+    },
+    /// Amounts are what was left in each vault and sent back to the admin.
+    Closed {
+        exchange_booth: Pubkey,
+        amount_a: u64,
+        amount_b: u64,
+    },
+    OracleUpdated {
+        exchange_booth: Pubkey,
+        oracle: Pubkey,
+        oracle_authority: Pubkey,
+    },
+    AdminProposed {
+        exchange_booth: Pubkey,
+        pending_admin: Pubkey,
+    },
+    AdminAccepted {
+        exchange_booth: Pubkey,
+        admin: Pubkey,
+    },
+    PausedSet {
+        exchange_booth: Pubkey,
+        paused: bool,
+    },
+    PublicLiquiditySet {
+        exchange_booth: Pubkey,
+        enabled: bool,
+    },
+    Migrated {
+        exchange_booth: Pubkey,
+        version: u8,
+    },
+}
+
+impl ExchangeBoothEvent {
+    pub fn emit(&self) -> ProgramResult {
+        sol_log_data(&[&self.try_to_vec()?]);
+        Ok(())
+    }
+}
+
+const DATA_PREFIX: &str = "Program data: ";
+
+/// Decodes the events `program_id` emitted, in order, from the log messages of a transaction.
+///
+/// The invocations in the logs are followed so that data logged by other programs, including ones
+/// called from the booth, is skipped.
+pub fn decode_events(program_id: &Pubkey, logs: &[String]) -> Vec<ExchangeBoothEvent> {
+    let program_id = program_id.to_string();
+    let mut invoke_stack: Vec<&str> = Vec::new();
+    let mut events = Vec::new();
+
+    for log in logs {
+        if let Some(data) = log.strip_prefix(DATA_PREFIX) {
+            if invoke_stack.last() == Some(&program_id.as_str()) {
+                events.extend(decode_event(data));
+            }
+        } else if let Some(rest) = log.strip_prefix("Program ") {
+            // only lines that start with a program id mark an invocation, a program may log
+            // `Program log: success` itself
+            let mut words = rest.split(' ');
+            let id = match words.next() {
+                Some(id) if id.parse::<Pubkey>().is_ok() => id,
+                _ => continue,
+            };
+            match words.next() {
+                Some("invoke") => invoke_stack.push(id),
+                Some("success") | Some("failed:") => {
+                    invoke_stack.pop();
+                }
+                _ => {}
+            }
+        }
+    }
+    events
+}
+
+/// Decodes the base64 payload of a single `Program data:` line.
+pub fn decode_event(data: &str) -> Option<ExchangeBoothEvent> {
+    // sol_log_data logs each field separately; events are always a single field
+    let bytes = base64::decode(data.split(' ').next()?).ok()?;
+    ExchangeBoothEvent::try_from_slice(&bytes).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn program_data(event: &ExchangeBoothEvent) -> std::io::Result<String> {
+        Ok(format!("{}{}", DATA_PREFIX, base64::encode(event.try_to_vec()?)))
+    }
+
+    #[test]
+    fn test_decode_events() -> std::io::Result<()> {
+        let exchange_booth_program_id = Pubkey::new_unique();
+        let token_program_id = Pubkey::new_unique();
+        let withdrew = ExchangeBoothEvent::Withdrew {
+            exchange_booth: Pubkey::new_unique(),
+            vault: Pubkey::new_unique(),
+            amount: 21,
+        };
+
+        // data logged from a CPI into another program belongs to that program
+        let logs: Vec<String> = vec![
+            format!("Program {} invoke [1]", exchange_booth_program_id),
+            "Program log: Instruction: Withdraw".to_string(),
+            format!("Program {} invoke [2]", token_program_id),
+            program_data(&withdrew)?,
+            format!("Program {} success", token_program_id),
+            program_data(&withdrew)?,
+            format!("Program {} consumed 4200 of 200000 compute units", exchange_booth_program_id),
+            format!("Program {} success", exchange_booth_program_id),
+        ];
+        assert_eq!(decode_events(&exchange_booth_program_id, &logs), vec![withdrew]);
+        assert!(decode_events(&Pubkey::new_unique(), &logs).is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn test_decode_events_nested() -> std::io::Result<()> {
+        let exchange_booth_program_id = Pubkey::new_unique();
+        let router_program_id = Pubkey::new_unique();
+        let token_program_id = Pubkey::new_unique();
+        let exchange_booth = Pubkey::new_unique();
+        let exchanged = ExchangeBoothEvent::Exchanged {
+            exchange_booth,
+            authority: Pubkey::new_unique(),
+            a_to_b: true,
+            amount_in: 21,
+            amount_out: 41,
+            fee: 1,
+        };
+        let paused = ExchangeBoothEvent::PausedSet { exchange_booth, paused: true };
+
+        // the booth called from another program, then called directly by the next instruction
+        let logs: Vec<String> = vec![
+            format!("Program {} invoke [1]", router_program_id),
+            program_data(&paused)?,
+            format!("Program {} invoke [2]", exchange_booth_program_id),
+            format!("Program {} invoke [3]", token_program_id),
+            program_data(&paused)?,
+            format!("Program {} success", token_program_id),
+            "Program log: success".to_string(),
+            program_data(&exchanged)?,
+            format!("Program {} success", exchange_booth_program_id),
+            program_data(&paused)?,
+            format!("Program {} success", router_program_id),
+            format!("Program {} invoke [1]", exchange_booth_program_id),
+            program_data(&paused)?,
+            format!("Program {} success", exchange_booth_program_id),
+        ];
+        assert_eq!(decode_events(&exchange_booth_program_id, &logs), vec![exchanged, paused.clone()]);
+        assert_eq!(decode_events(&router_program_id, &logs), vec![paused.clone(), paused]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_decode_events_failed_cpi() -> std::io::Result<()> {
+        let exchange_booth_program_id = Pubkey::new_unique();
+        let router_program_id = Pubkey::new_unique();
+        let exchange_booth = Pubkey::new_unique();
+        let paused = ExchangeBoothEvent::PausedSet { exchange_booth, paused: true };
+        let unpaused = ExchangeBoothEvent::PausedSet { exchange_booth, paused: false };
+
+        // a failed invocation is popped like a successful one; a caller never logs anything after
+        // its CPI fails, the router only does here to show whose data follows
+        let logs: Vec<String> = vec![
+            format!("Program {} invoke [1]", router_program_id),
+            format!("Program {} invoke [2]", exchange_booth_program_id),
+            program_data(&paused)?,
+            format!("Program {} failed: custom program error: 0x11", exchange_booth_program_id),
+            program_data(&unpaused)?,
+            format!("Program {} failed: custom program error: 0x11", router_program_id),
+        ];
+        assert_eq!(decode_events(&exchange_booth_program_id, &logs), vec![paused]);
+        assert_eq!(decode_events(&router_program_id, &logs), vec![unpaused]);
+        Ok(())
+    }
+}
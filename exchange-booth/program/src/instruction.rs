@@ -1,12 +1,19 @@
 use borsh::{BorshDeserialize, BorshSerialize};
+use solana_program::instruction::{AccountMeta, Instruction};
 use solana_program::pubkey::Pubkey;
+use solana_program::{system_program, sysvar};
 
-// TODO numeric overflow / rounding
+use crate::pda::{find_exchange_booth_address, find_vault_address};
 
 #[derive(BorshSerialize, BorshDeserialize, Debug, Clone)]
 pub enum ExchangeBoothInstruction {
     // named arguments?
-    InitializeExchangeBooth,
+    InitializeExchangeBooth {
+        fee_bps: u16,
+        max_staleness_slots: u64,
+        oracle_program_id: Pubkey,
+        oracle_authority: Pubkey,
+    },
     Deposit {
         amount: u64
     },
@@ -14,7 +21,362 @@ pub enum ExchangeBoothInstruction {
         amount: u64
     },
     Exchange {
-        amount: u64
+        amount: u64,
+        min_amount_out: u64,
+    },
+    ExchangeExactOut {
+        amount_out: u64,
+        max_amount_in: u64,
     },
     CloseExchangeBooth,
+    UpdateOracle {
+        oracle_authority: Pubkey,
+    },
+    ProposeAdmin {
+        new_admin: Pubkey,
+    },
+    AcceptAdmin,
+    SetPaused {
+        paused: bool,
+    },
+    MigrateExchangeBooth {
+        fee_bps: u16,
+        max_staleness_slots: u64,
+        oracle_program_id: Pubkey,
+        oracle_authority: Pubkey,
+    },
+    SetPublicLiquidity {
+        enabled: bool,
+    },
+    Quote {
+        amount: u64,
+        direction: Direction,
+    },
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, Copy, PartialEq)]
+pub enum Direction {
+    AToB,
+    BToA,
+}
+
+/// Return data of `Quote`: what an `Exchange` of the quoted amount would pay out right now.
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, PartialEq)]
+pub struct QuoteResult {
+    pub amount_out: u64,
+    pub fee: u64,
+    /// Oracle rate and update slot the quote was priced at.
+    pub mantissa: u64,
+    pub exponent: i32,
+    pub last_update_slot: u64,
+}
+
+impl QuoteResult {
+    /// Reads the quote back out of the log messages of a simulated `Quote` transaction.
+    pub fn from_logs(program_id: &Pubkey, logs: &[String]) -> Option<QuoteResult> {
+        let prefix = format!("Program return: {} ", program_id);
+        let data = logs.iter().find_map(|log| log.strip_prefix(&prefix))?;
+        QuoteResult::try_from_slice(&base64::decode(data).ok()?).ok()
+    }
+}
+
+/// Creates the booth `admin` runs for `mint_a` and `mint_b`, along with its two vaults. The admin
+/// pays for all three accounts.
+#[allow(clippy::too_many_arguments)]
+pub fn initialize_exchange_booth(
+    program_id: &Pubkey,
+    admin: &Pubkey,
+    mint_a: &Pubkey,
+    mint_b: &Pubkey,
+    oracle: &Pubkey,
+    fee_bps: u16,
+    max_staleness_slots: u64,
+    oracle_program_id: &Pubkey,
+    oracle_authority: &Pubkey,
+) -> Instruction {
+    let (exchange_booth, _) = find_exchange_booth_address(program_id, admin, mint_a, mint_b);
+    let (vault_a, _) = find_vault_address(program_id, &exchange_booth, mint_a);
+    let (vault_b, _) = find_vault_address(program_id, &exchange_booth, mint_b);
+    Instruction::new_with_borsh(
+        *program_id,
+        &ExchangeBoothInstruction::InitializeExchangeBooth {
+            fee_bps,
+            max_staleness_slots,
+            oracle_program_id: *oracle_program_id,
+            oracle_authority: *oracle_authority,
+        },
+        vec![
+            AccountMeta::new(*admin, true),
+            AccountMeta::new_readonly(*mint_a, false),
+            AccountMeta::new_readonly(*mint_b, false),
+            AccountMeta::new(vault_a, false),
+            AccountMeta::new(vault_b, false),
+            AccountMeta::new_readonly(*oracle, false),
+            AccountMeta::new(exchange_booth, false),
+            AccountMeta::new_readonly(system_program::id(), false),
+            AccountMeta::new_readonly(spl_token::id(), false),
+            AccountMeta::new_readonly(sysvar::rent::id(), false),
+        ],
+    )
+}
+
+/// Moves `amount` of `mint` from `src` into the booth's vault for it.
+pub fn deposit(
+    program_id: &Pubkey,
+    exchange_booth: &Pubkey,
+    mint: &Pubkey,
+    src: &Pubkey,
+    authority: &Pubkey,
+    amount: u64,
+) -> Instruction {
+    let (vault, _) = find_vault_address(program_id, exchange_booth, mint);
+    Instruction::new_with_borsh(
+        *program_id,
+        &ExchangeBoothInstruction::Deposit { amount },
+        vec![
+            AccountMeta::new(*src, false),
+            AccountMeta::new(vault, false),
+            AccountMeta::new_readonly(*authority, true),
+            AccountMeta::new(*exchange_booth, false),
+            AccountMeta::new_readonly(spl_token::id(), false),
+        ],
+    )
+}
+
+/// Moves `amount` out of the booth's vault for `mint` into `dst`.
+pub fn withdraw(
+    program_id: &Pubkey,
+    exchange_booth: &Pubkey,
+    mint: &Pubkey,
+    dst: &Pubkey,
+    admin: &Pubkey,
+    amount: u64,
+) -> Instruction {
+    let (vault, _) = find_vault_address(program_id, exchange_booth, mint);
+    Instruction::new_with_borsh(
+        *program_id,
+        &ExchangeBoothInstruction::Withdraw { amount },
+        vec![
+            AccountMeta::new(vault, false),
+            AccountMeta::new(*dst, false),
+            AccountMeta::new_readonly(*mint, false),
+            AccountMeta::new_readonly(*admin, true),
+            AccountMeta::new(*exchange_booth, false),
+            AccountMeta::new_readonly(spl_token::id(), false),
+        ],
+    )
+}
+
+/// Swaps exactly `amount` of `deposit_mint` from `deposit_src` for at least `min_amount_out` of
+/// `withdraw_mint`, paid into `withdraw_dst`.
+#[allow(clippy::too_many_arguments)]
+pub fn exchange(
+    program_id: &Pubkey,
+    exchange_booth: &Pubkey,
+    oracle: &Pubkey,
+    deposit_mint: &Pubkey,
+    withdraw_mint: &Pubkey,
+    deposit_src: &Pubkey,
+    withdraw_dst: &Pubkey,
+    authority: &Pubkey,
+    amount: u64,
+    min_amount_out: u64,
+) -> Instruction {
+    Instruction::new_with_borsh(
+        *program_id,
+        &ExchangeBoothInstruction::Exchange { amount, min_amount_out },
+        swap_accounts(program_id, exchange_booth, oracle, deposit_mint, withdraw_mint, deposit_src, withdraw_dst, authority),
+    )
+}
+
+/// Swaps at most `max_amount_in` of `deposit_mint` from `deposit_src` for exactly `amount_out`
+/// of `withdraw_mint`, paid into `withdraw_dst`.
+#[allow(clippy::too_many_arguments)]
+pub fn exchange_exact_out(
+    program_id: &Pubkey,
+    exchange_booth: &Pubkey,
+    oracle: &Pubkey,
+    deposit_mint: &Pubkey,
+    withdraw_mint: &Pubkey,
+    deposit_src: &Pubkey,
+    withdraw_dst: &Pubkey,
+    authority: &Pubkey,
+    amount_out: u64,
+    max_amount_in: u64,
+) -> Instruction {
+    Instruction::new_with_borsh(
+        *program_id,
+        &ExchangeBoothInstruction::ExchangeExactOut { amount_out, max_amount_in },
+        swap_accounts(program_id, exchange_booth, oracle, deposit_mint, withdraw_mint, deposit_src, withdraw_dst, authority),
+    )
+}
+
+#[allow(clippy::too_many_arguments)]
+fn swap_accounts(
+    program_id: &Pubkey,
+    exchange_booth: &Pubkey,
+    oracle: &Pubkey,
+    deposit_mint: &Pubkey,
+    withdraw_mint: &Pubkey,
+    deposit_src: &Pubkey,
+    withdraw_dst: &Pubkey,
+    authority: &Pubkey,
+) -> Vec<AccountMeta> {
+    let (deposit_vault, _) = find_vault_address(program_id, exchange_booth, deposit_mint);
+    let (withdraw_vault, _) = find_vault_address(program_id, exchange_booth, withdraw_mint);
+    vec![
+        AccountMeta::new(*deposit_src, false),
+        AccountMeta::new(deposit_vault, false),
+        AccountMeta::new(withdraw_vault, false),
+        AccountMeta::new(*withdraw_dst, false),
+        AccountMeta::new_readonly(*deposit_mint, false),
+        AccountMeta::new_readonly(*withdraw_mint, false),
+        AccountMeta::new_readonly(*authority, true),
+        AccountMeta::new_readonly(*oracle, false),
+        AccountMeta::new(*exchange_booth, false),
+        AccountMeta::new_readonly(spl_token::id(), false),
+    ]
+}
+
+/// Pays both vaults out to `dst_a` and `dst_b`, then closes the vaults and the booth and returns
+/// their rent to the admin.
+pub fn close_exchange_booth(
+    program_id: &Pubkey,
+    exchange_booth: &Pubkey,
+    admin: &Pubkey,
+    mint_a: &Pubkey,
+    mint_b: &Pubkey,
+    dst_a: &Pubkey,
+    dst_b: &Pubkey,
+) -> Instruction {
+    let (vault_a, _) = find_vault_address(program_id, exchange_booth, mint_a);
+    let (vault_b, _) = find_vault_address(program_id, exchange_booth, mint_b);
+    Instruction::new_with_borsh(
+        *program_id,
+        &ExchangeBoothInstruction::CloseExchangeBooth,
+        vec![
+            AccountMeta::new(*admin, true),
+            AccountMeta::new(*exchange_booth, false),
+            AccountMeta::new(vault_a, false),
+            AccountMeta::new(vault_b, false),
+            AccountMeta::new(*dst_a, false),
+            AccountMeta::new(*dst_b, false),
+            AccountMeta::new_readonly(spl_token::id(), false),
+        ],
+    )
+}
+
+/// Points the booth at a new oracle, published by `oracle_authority`.
+pub fn update_oracle(
+    program_id: &Pubkey,
+    exchange_booth: &Pubkey,
+    admin: &Pubkey,
+    oracle: &Pubkey,
+    oracle_authority: &Pubkey,
+) -> Instruction {
+    Instruction::new_with_borsh(
+        *program_id,
+        &ExchangeBoothInstruction::UpdateOracle { oracle_authority: *oracle_authority },
+        vec![
+            AccountMeta::new_readonly(*admin, true),
+            AccountMeta::new(*exchange_booth, false),
+            AccountMeta::new_readonly(*oracle, false),
+        ],
+    )
+}
+
+pub fn propose_admin(program_id: &Pubkey, exchange_booth: &Pubkey, admin: &Pubkey, new_admin: &Pubkey) -> Instruction {
+    Instruction::new_with_borsh(
+        *program_id,
+        &ExchangeBoothInstruction::ProposeAdmin { new_admin: *new_admin },
+        admin_accounts(exchange_booth, admin),
+    )
+}
+
+pub fn accept_admin(program_id: &Pubkey, exchange_booth: &Pubkey, pending_admin: &Pubkey) -> Instruction {
+    Instruction::new_with_borsh(
+        *program_id,
+        &ExchangeBoothInstruction::AcceptAdmin,
+        admin_accounts(exchange_booth, pending_admin),
+    )
+}
+
+pub fn set_paused(program_id: &Pubkey, exchange_booth: &Pubkey, admin: &Pubkey, paused: bool) -> Instruction {
+    Instruction::new_with_borsh(
+        *program_id,
+        &ExchangeBoothInstruction::SetPaused { paused },
+        admin_accounts(exchange_booth, admin),
+    )
+}
+
+/// Rewrites the legacy booth at `exchange_booth` in the latest layout. The admin pays the extra
+/// rent.
+#[allow(clippy::too_many_arguments)]
+pub fn migrate_exchange_booth(
+    program_id: &Pubkey,
+    exchange_booth: &Pubkey,
+    admin: &Pubkey,
+    mint_a: &Pubkey,
+    mint_b: &Pubkey,
+    fee_bps: u16,
+    max_staleness_slots: u64,
+    oracle_program_id: &Pubkey,
+    oracle_authority: &Pubkey,
+) -> Instruction {
+    let (vault_a, _) = find_vault_address(program_id, exchange_booth, mint_a);
+    let (vault_b, _) = find_vault_address(program_id, exchange_booth, mint_b);
+    Instruction::new_with_borsh(
+        *program_id,
+        &ExchangeBoothInstruction::MigrateExchangeBooth {
+            fee_bps,
+            max_staleness_slots,
+            oracle_program_id: *oracle_program_id,
+            oracle_authority: *oracle_authority,
+        },
+        vec![
+            AccountMeta::new(*admin, true),
+            AccountMeta::new(*exchange_booth, false),
+            AccountMeta::new_readonly(vault_a, false),
+            AccountMeta::new_readonly(vault_b, false),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+    )
+}
+
+pub fn set_public_liquidity(program_id: &Pubkey, exchange_booth: &Pubkey, admin: &Pubkey, enabled: bool) -> Instruction {
+    Instruction::new_with_borsh(
+        *program_id,
+        &ExchangeBoothInstruction::SetPublicLiquidity { enabled },
+        admin_accounts(exchange_booth, admin),
+    )
+}
+
+/// Prices an exchange of `amount` without making it. Simulate the transaction and read the
+/// result back with [`QuoteResult::from_logs`].
+pub fn quote(
+    program_id: &Pubkey,
+    exchange_booth: &Pubkey,
+    oracle: &Pubkey,
+    mint_a: &Pubkey,
+    mint_b: &Pubkey,
+    amount: u64,
+    direction: Direction,
+) -> Instruction {
+    Instruction::new_with_borsh(
+        *program_id,
+        &ExchangeBoothInstruction::Quote { amount, direction },
+        vec![
+            AccountMeta::new_readonly(*exchange_booth, false),
+            AccountMeta::new_readonly(*oracle, false),
+            AccountMeta::new_readonly(*mint_a, false),
+            AccountMeta::new_readonly(*mint_b, false),
+        ],
+    )
+}
+
+fn admin_accounts(exchange_booth: &Pubkey, signer: &Pubkey) -> Vec<AccountMeta> {
+    vec![
+        AccountMeta::new_readonly(*signer, true),
+        AccountMeta::new(*exchange_booth, false),
+    ]
 }
@@ -1,5 +1,10 @@
+#[cfg(not(feature = "no-entrypoint"))]
 pub mod entrypoint;
 pub mod error;
+pub mod events;
 pub mod instruction;
+pub mod pda;
+pub mod pricing;
 pub mod processor;
 pub mod state;
+pub mod validation;
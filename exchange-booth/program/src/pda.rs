@@ -0,0 +1,39 @@
+//! Program addresses used by the exchange booth.
+
+use solana_program::pubkey::Pubkey;
+
+/// Address and bump seed of the booth `admin` runs for `mint_a` and `mint_b`.
+///
+/// The address is fixed at init, so it keeps being derived from the original admin after
+/// `AcceptAdmin` hands the booth over.
+pub fn find_exchange_booth_address(
+    program_id: &Pubkey,
+    admin: &Pubkey,
+    mint_a: &Pubkey,
+    mint_b: &Pubkey,
+) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[b"exchange_booth", admin.as_ref(), mint_a.as_ref(), mint_b.as_ref()],
+        program_id,
+    )
+}
+
+/// Address and bump seed of a booth created before `MigrateExchangeBooth` existed, which also
+/// had its oracle in the seeds.
+pub fn find_legacy_exchange_booth_address(
+    program_id: &Pubkey,
+    admin: &Pubkey,
+    mint_a: &Pubkey,
+    mint_b: &Pubkey,
+    oracle: &Pubkey,
+) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[b"exchange_booth", admin.as_ref(), mint_a.as_ref(), mint_b.as_ref(), oracle.as_ref()],
+        program_id,
+    )
+}
+
+/// Address and bump seed of the booth's vault for `mint`. Each vault is its own token authority.
+pub fn find_vault_address(program_id: &Pubkey, exchange_booth: &Pubkey, mint: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[exchange_booth.as_ref(), mint.as_ref()], program_id)
+}
@@ -0,0 +1,359 @@
+//! Exchange pricing shared by the program and off-chain clients.
+//!
+//! Everything here is plain integer math over `core`, with no accounts or syscalls, so the same
+//! code can be linked into `no_std` or off-chain consumers. All intermediate products are checked
+//! `u128`, and every rounding choice is made explicitly and in the booth's favour.
+
+/// Basis points in a whole.
+pub const BPS: u16 = 10_000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PricingError {
+    Overflow,
+    /// The oracle published a zero rate, which cannot be inverted.
+    ZeroRate,
+    /// A fee of `BPS` or more leaves nothing to gross up.
+    InvalidFee,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Rounding {
+    Down,
+    Up,
+}
+
+/// Exchange rate between base units of two mints, as an exact fraction.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Rate {
+    pub numerator: u128,
+    pub denominator: u128,
+}
+
+impl Rate {
+    /// Rate from base units of mint_a to base units of mint_b for an oracle price of
+    /// `mantissa * 10^exponent` whole mint_b tokens per whole mint_a token.
+    pub fn from_oracle(
+        mantissa: u64,
+        exponent: i32,
+        decimals_a: u8,
+        decimals_b: u8,
+    ) -> Result<Rate, PricingError> {
+        if mantissa == 0 {
+            return Err(PricingError::ZeroRate);
+        }
+
+        // base_b = base_a * mantissa * 10^(exponent + decimals_b - decimals_a)
+        let scale = exponent
+            .checked_add(decimals_b as i32 - decimals_a as i32)
+            .ok_or(PricingError::Overflow)?;
+        let factor = 10u128
+            .checked_pow(scale.unsigned_abs())
+            .ok_or(PricingError::Overflow)?;
+        let mantissa = mantissa as u128;
+        if scale >= 0 {
+            Ok(Rate {
+                numerator: mantissa.checked_mul(factor).ok_or(PricingError::Overflow)?,
+                denominator: 1,
+            })
+        } else {
+            Ok(Rate { numerator: mantissa, denominator: factor })
+        }
+    }
+
+    /// The rate in the other direction.
+    pub fn inverse(self) -> Rate {
+        Rate { numerator: self.denominator, denominator: self.numerator }
+    }
+
+    /// Converts `amount` at this rate.
+    pub fn convert(self, amount: u64, rounding: Rounding) -> Result<u64, PricingError> {
+        mul_div(amount, self.numerator, self.denominator, rounding)
+    }
+}
+
+/// `amount * numerator / denominator`, rounded as asked.
+pub fn mul_div(
+    amount: u64,
+    numerator: u128,
+    denominator: u128,
+    rounding: Rounding,
+) -> Result<u64, PricingError> {
+    if denominator == 0 {
+        return Err(PricingError::ZeroRate);
+    }
+    let product = (amount as u128)
+        .checked_mul(numerator)
+        .ok_or(PricingError::Overflow)?;
+    let quotient = match rounding {
+        Rounding::Down => product / denominator,
+        Rounding::Up => product / denominator + (product % denominator != 0) as u128,
+    };
+    u64::try_from(quotient).map_err(|_| PricingError::Overflow)
+}
+
+/// Checks that a booth may charge `fee_bps`. A fee of a whole `BPS` would keep the entire output
+/// of every exchange.
+pub fn check_fee_bps(fee_bps: u16) -> Result<(), PricingError> {
+    if fee_bps >= BPS {
+        return Err(PricingError::InvalidFee);
+    }
+    Ok(())
+}
+
+/// Fee of `fee_bps` on `amount`, rounded up so the booth never undercharges.
+pub fn fee(amount: u64, fee_bps: u16) -> Result<u64, PricingError> {
+    mul_div(amount, fee_bps as u128, BPS as u128, Rounding::Up)
+}
+
+/// Smallest amount that still leaves at least `net` once its fee of `fee_bps` is taken.
+pub fn gross_up(net: u64, fee_bps: u16) -> Result<u64, PricingError> {
+    check_fee_bps(fee_bps)?;
+    let mut gross = mul_div(net, BPS as u128, (BPS - fee_bps) as u128, Rounding::Up)?;
+    // the fee rounds up, so the closed form can land one short
+    while gross - fee(gross, fee_bps)? < net {
+        gross = gross.checked_add(1).ok_or(PricingError::Overflow)?;
+    }
+    Ok(gross)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Quote {
+    pub amount_in: u64,
+    /// Paid out to the user, after the fee.
+    pub amount_out: u64,
+    /// Kept by the booth, in units of the output mint.
+    pub fee: u64,
+}
+
+/// Prices a swap of exactly `amount_in`. The output rounds down and the fee rounds up.
+pub fn quote_exact_in(rate: Rate, amount_in: u64, fee_bps: u16) -> Result<Quote, PricingError> {
+    let gross_amount_out = rate.convert(amount_in, Rounding::Down)?;
+    let fee = fee(gross_amount_out, fee_bps)?;
+    Ok(Quote { amount_in, amount_out: gross_amount_out - fee, fee })
+}
+
+/// Prices a swap paying out exactly `amount_out`, charging the smallest input that covers it
+/// and its fee.
+pub fn quote_exact_out(rate: Rate, amount_out: u64, fee_bps: u16) -> Result<Quote, PricingError> {
+    let gross_amount_out = gross_up(amount_out, fee_bps)?;
+    let amount_in = rate.inverse().convert(gross_amount_out, Rounding::Up)?;
+    Ok(Quote { amount_in, amount_out, fee: gross_amount_out - amount_out })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Small deterministic xorshift generator, so the property tests need no extra crates.
+    struct Rng(u64);
+
+    impl Rng {
+        fn next(&mut self) -> u64 {
+            self.0 ^= self.0 << 13;
+            self.0 ^= self.0 >> 7;
+            self.0 ^= self.0 << 17;
+            self.0
+        }
+
+        fn below(&mut self, bound: u64) -> u64 {
+            self.next() % bound
+        }
+    }
+
+    const FEES: [u16; 6] = [0, 1, 30, 100, 2_500, 9_999];
+
+    fn rates() -> Vec<Rate> {
+        let mut rates = Vec::new();
+        for &(mantissa, exponent) in &[(1, 0), (2, 0), (20, -1), (3, -2), (7, 1), (12_345, -4), (1, -6)] {
+            for &(decimals_a, decimals_b) in &[(0, 0), (6, 9), (9, 6), (2, 0)] {
+                let rate = Rate::from_oracle(mantissa, exponent, decimals_a, decimals_b).unwrap();
+                rates.push(rate);
+                rates.push(rate.inverse());
+            }
+        }
+        rates
+    }
+
+    /// `amount` converted at `rate` with no rounding, compared against `paid` by cross
+    /// multiplication: is `paid <= amount * rate`?
+    fn within_fair_value(rate: Rate, amount: u64, paid: u64) -> bool {
+        paid as u128 * rate.denominator <= amount as u128 * rate.numerator
+    }
+
+    #[test]
+    fn test_from_oracle() {
+        assert_eq!(Rate::from_oracle(20, -1, 0, 0), Ok(Rate { numerator: 20, denominator: 10 }));
+        assert_eq!(Rate::from_oracle(2, 0, 6, 9), Ok(Rate { numerator: 2_000, denominator: 1 }));
+        assert_eq!(Rate::from_oracle(2, 0, 9, 6), Ok(Rate { numerator: 2, denominator: 1_000 }));
+        assert_eq!(Rate::from_oracle(5, 2, 0, 0), Ok(Rate { numerator: 500, denominator: 1 }));
+        assert_eq!(Rate::from_oracle(0, 0, 0, 0), Err(PricingError::ZeroRate));
+        assert_eq!(Rate::from_oracle(1, 39, 0, 0), Err(PricingError::Overflow));
+        assert_eq!(Rate::from_oracle(1, i32::MAX, 0, 9), Err(PricingError::Overflow));
+        assert_eq!(Rate::from_oracle(u64::MAX, 20, 0, 0), Err(PricingError::Overflow));
+    }
+
+    #[test]
+    fn test_inverse() {
+        let rate = Rate::from_oracle(20, -1, 0, 0).unwrap();
+        assert_eq!(rate.inverse(), Rate { numerator: 10, denominator: 20 });
+        assert_eq!(rate.inverse().inverse(), rate);
+    }
+
+    #[test]
+    fn test_mul_div_rounding() {
+        assert_eq!(mul_div(7, 1, 2, Rounding::Down), Ok(3));
+        assert_eq!(mul_div(7, 1, 2, Rounding::Up), Ok(4));
+        assert_eq!(mul_div(8, 1, 2, Rounding::Up), Ok(4));
+        assert_eq!(mul_div(0, 5, 3, Rounding::Up), Ok(0));
+        assert_eq!(mul_div(1, 1, 0, Rounding::Down), Err(PricingError::ZeroRate));
+        assert_eq!(mul_div(u64::MAX, 1, 1, Rounding::Up), Ok(u64::MAX));
+        assert_eq!(mul_div(u64::MAX, 2, 1, Rounding::Down), Err(PricingError::Overflow));
+        assert_eq!(mul_div(u64::MAX, u128::MAX, 1, Rounding::Down), Err(PricingError::Overflow));
+    }
+
+    #[test]
+    fn test_fee() {
+        assert_eq!(fee(42, 100), Ok(1));
+        assert_eq!(fee(100, 100), Ok(1));
+        assert_eq!(fee(101, 100), Ok(2));
+        assert_eq!(fee(0, 100), Ok(0));
+        assert_eq!(fee(42, 0), Ok(0));
+        assert_eq!(fee(42, BPS), Ok(42));
+    }
+
+    #[test]
+    fn test_check_fee_bps() {
+        assert_eq!(check_fee_bps(0), Ok(()));
+        assert_eq!(check_fee_bps(BPS - 1), Ok(()));
+        assert_eq!(check_fee_bps(BPS), Err(PricingError::InvalidFee));
+        assert_eq!(check_fee_bps(u16::MAX), Err(PricingError::InvalidFee));
+    }
+
+    #[test]
+    fn test_gross_up() {
+        assert_eq!(gross_up(10, 100), Ok(11));
+        assert_eq!(gross_up(0, 100), Ok(0));
+        assert_eq!(gross_up(42, 0), Ok(42));
+        assert_eq!(gross_up(1, BPS), Err(PricingError::InvalidFee));
+        assert_eq!(gross_up(u64::MAX, 100), Err(PricingError::Overflow));
+    }
+
+    #[test]
+    fn test_quotes() {
+        // the amounts the integration test trades at a rate of 2.0 and a 1% fee
+        let rate = Rate::from_oracle(20, -1, 0, 0).unwrap();
+        assert_eq!(quote_exact_in(rate, 21, 100), Ok(Quote { amount_in: 21, amount_out: 41, fee: 1 }));
+        assert_eq!(quote_exact_in(rate.inverse(), 20, 100), Ok(Quote { amount_in: 20, amount_out: 9, fee: 1 }));
+        assert_eq!(quote_exact_out(rate, 10, 100), Ok(Quote { amount_in: 6, amount_out: 10, fee: 1 }));
+    }
+
+    #[test]
+    fn test_gross_up_is_minimal() {
+        for &fee_bps in &FEES {
+            for net in 0..5_000 {
+                let gross = gross_up(net, fee_bps).unwrap();
+                assert!(gross - fee(gross, fee_bps).unwrap() >= net);
+                if gross > 0 {
+                    assert!(gross - 1 - fee(gross - 1, fee_bps).unwrap() < net);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_exact_in_monotonic() {
+        for rate in rates() {
+            for &fee_bps in &FEES {
+                let mut previous = 0;
+                for amount_in in 0..2_000 {
+                    let amount_out = quote_exact_in(rate, amount_in, fee_bps).unwrap().amount_out;
+                    assert!(amount_out >= previous, "{:?} {} {}", rate, fee_bps, amount_in);
+                    previous = amount_out;
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_exact_out_monotonic() {
+        for rate in rates() {
+            for &fee_bps in &FEES {
+                let mut previous = 0;
+                for amount_out in 0..2_000 {
+                    let amount_in = match quote_exact_out(rate, amount_out, fee_bps) {
+                        Ok(quote) => quote.amount_in,
+                        Err(PricingError::Overflow) => break,
+                        Err(err) => panic!("{:?}", err),
+                    };
+                    assert!(amount_in >= previous, "{:?} {} {}", rate, fee_bps, amount_out);
+                    previous = amount_in;
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_never_pays_more_than_fair_value() {
+        let mut rng = Rng(0x2545_f491_4f6c_dd1d);
+        for rate in rates() {
+            for &fee_bps in &FEES {
+                for _ in 0..2_000 {
+                    let amount = rng.below(1 << 40);
+
+                    // large amounts at the extreme rates overflow u64, which is an error rather
+                    // than a bad price
+                    for quote in [quote_exact_in(rate, amount, fee_bps), quote_exact_out(rate, amount, fee_bps)] {
+                        match quote {
+                            Ok(quote) => assert!(within_fair_value(rate, quote.amount_in, quote.amount_out + quote.fee)),
+                            Err(PricingError::Overflow) => {}
+                            Err(err) => panic!("{:?}", err),
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_exact_out_charges_the_smallest_input() {
+        let mut rng = Rng(0x9e37_79b9_7f4a_7c15);
+        for rate in rates() {
+            for &fee_bps in &FEES {
+                for _ in 0..2_000 {
+                    let amount_out = rng.below(1 << 32);
+                    let quote = match quote_exact_out(rate, amount_out, fee_bps) {
+                        Ok(quote) => quote,
+                        Err(PricingError::Overflow) => continue,
+                        Err(err) => panic!("{:?}", err),
+                    };
+                    // paying the quoted input exact-in covers the output, one less does not cover
+                    // the gross output the booth had to pay out
+                    let exact_in = quote_exact_in(rate, quote.amount_in, fee_bps).unwrap();
+                    assert!(exact_in.amount_out + exact_in.fee >= quote.amount_out + quote.fee);
+                    if quote.amount_in > 0 {
+                        let gross = rate.convert(quote.amount_in - 1, Rounding::Down).unwrap();
+                        assert!(gross < quote.amount_out + quote.fee);
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_round_trip_never_profitable() {
+        let mut rng = Rng(0xdead_beef_cafe_f00d);
+        for rate in rates() {
+            for &fee_bps in &FEES {
+                for amount in (0..500).chain((0..2_000).map(|_| rng.below(1 << 40))) {
+                    let there = match quote_exact_in(rate, amount, fee_bps) {
+                        Ok(quote) => quote,
+                        Err(PricingError::Overflow) => continue,
+                        Err(err) => panic!("{:?}", err),
+                    };
+                    let back = quote_exact_in(rate.inverse(), there.amount_out, fee_bps).unwrap();
+                    assert!(back.amount_out <= amount, "{:?} {} {}", rate, fee_bps, amount);
+                }
+            }
+        }
+    }
+}
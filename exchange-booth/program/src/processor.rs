@@ -6,10 +6,18 @@ use solana_program::{
 
 use crate::instruction::ExchangeBoothInstruction;
 
+pub mod accept_admin;
 pub mod close_exchange_booth;
 pub mod deposit;
 pub mod exchange;
+pub mod exchange_exact_out;
 pub mod initialize_exchange_booth;
+pub mod migrate_exchange_booth;
+pub mod propose_admin;
+pub mod quote;
+pub mod set_paused;
+pub mod set_public_liquidity;
+pub mod update_oracle;
 pub mod withdraw;
 
 pub struct Processor {}
@@ -24,25 +32,81 @@ impl Processor {
             .map_err(|_| ProgramError::InvalidInstructionData)?;
 
         match instruction {
-            ExchangeBoothInstruction::InitializeExchangeBooth => {
+            ExchangeBoothInstruction::InitializeExchangeBooth {
+                fee_bps,
+                max_staleness_slots,
+                oracle_program_id,
+                oracle_authority,
+            } => {
                 msg!("Instruction: InitializeExchangeBooth");
-                initialize_exchange_booth::process(program_id, accounts)?;
+                initialize_exchange_booth::process(
+                    program_id,
+                    accounts,
+                    fee_bps,
+                    max_staleness_slots,
+                    oracle_program_id,
+                    oracle_authority,
+                )?;
             }
             ExchangeBoothInstruction::Deposit { amount } => {
                 msg!("Instruction: Deposit");
-                deposit::process(accounts, amount)?;
+                deposit::process(program_id, accounts, amount)?;
             }
             ExchangeBoothInstruction::Withdraw { amount } => {
                 msg!("Instruction: Withdraw");
                 withdraw::process(program_id, accounts, amount)?;
             }
-            ExchangeBoothInstruction::Exchange { amount } => {
+            ExchangeBoothInstruction::Exchange { amount, min_amount_out } => {
                 msg!("Instruction: Exchange");
-                exchange::process(program_id, accounts, amount)?;
+                exchange::process(program_id, accounts, amount, min_amount_out)?;
+            }
+            ExchangeBoothInstruction::ExchangeExactOut { amount_out, max_amount_in } => {
+                msg!("Instruction: ExchangeExactOut");
+                exchange_exact_out::process(program_id, accounts, amount_out, max_amount_in)?;
             }
             ExchangeBoothInstruction::CloseExchangeBooth => {
                 msg!("Instruction: CloseExchangeBooth");
-                close_exchange_booth::process(accounts)?;
+                close_exchange_booth::process(program_id, accounts)?;
+            }
+            ExchangeBoothInstruction::UpdateOracle { oracle_authority } => {
+                msg!("Instruction: UpdateOracle");
+                update_oracle::process(program_id, accounts, oracle_authority)?;
+            }
+            ExchangeBoothInstruction::ProposeAdmin { new_admin } => {
+                msg!("Instruction: ProposeAdmin");
+                propose_admin::process(program_id, accounts, new_admin)?;
+            }
+            ExchangeBoothInstruction::AcceptAdmin => {
+                msg!("Instruction: AcceptAdmin");
+                accept_admin::process(program_id, accounts)?;
+            }
+            ExchangeBoothInstruction::SetPaused { paused } => {
+                msg!("Instruction: SetPaused");
+                set_paused::process(program_id, accounts, paused)?;
+            }
+            ExchangeBoothInstruction::MigrateExchangeBooth {
+                fee_bps,
+                max_staleness_slots,
+                oracle_program_id,
+                oracle_authority,
+            } => {
+                msg!("Instruction: MigrateExchangeBooth");
+                migrate_exchange_booth::process(
+                    program_id,
+                    accounts,
+                    fee_bps,
+                    max_staleness_slots,
+                    oracle_program_id,
+                    oracle_authority,
+                )?;
+            }
+            ExchangeBoothInstruction::SetPublicLiquidity { enabled } => {
+                msg!("Instruction: SetPublicLiquidity");
+                set_public_liquidity::process(program_id, accounts, enabled)?;
+            }
+            ExchangeBoothInstruction::Quote { amount, direction } => {
+                msg!("Instruction: Quote");
+                quote::process(program_id, accounts, amount, direction)?;
             }
         }
 
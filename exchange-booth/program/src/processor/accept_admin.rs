@@ -0,0 +1,37 @@
+use solana_program::account_info::AccountInfo;
+use solana_program::entrypoint::ProgramResult;
+use solana_program::pubkey::Pubkey;
+
+use crate::{
+    error::ExchangeBoothError,
+    events::ExchangeBoothEvent,
+    state::ExchangeBooth,
+    validation::{next_account, AccountSpec},
+};
+
+pub fn process(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    let accounts = &mut accounts.iter();
+
+    let pending_admin = next_account(accounts, AccountSpec::new().signer())?;
+    let exchange_booth = next_account(accounts, AccountSpec::new().writable().owner(program_id))?;
+
+    let mut exchange_booth_data = ExchangeBooth::load_mut(exchange_booth)?;
+    if exchange_booth_data.pending_admin == Pubkey::default()
+        || *pending_admin.key != exchange_booth_data.pending_admin
+    {
+        return Err(ExchangeBoothError::IncorrectPendingAdmin.into());
+    }
+
+    exchange_booth_data.admin = exchange_booth_data.pending_admin;
+    exchange_booth_data.pending_admin = Pubkey::default();
+
+    ExchangeBoothEvent::AdminAccepted {
+        exchange_booth: *exchange_booth.key,
+        admin: exchange_booth_data.admin,
+    }.emit()?;
+
+    Ok(())
+}
@@ -1,29 +1,109 @@
-use solana_program::{};
-
 use crate::{
     error::ExchangeBoothError,
+    events::ExchangeBoothEvent,
     state::ExchangeBooth,
+    validation::{check_program_address_with_bump, next_account, AccountSpec},
 };
 
-use borsh::{BorshDeserialize, BorshSerialize};
-use solana_program::account_info::{AccountInfo, next_account_info};
+use solana_program::account_info::AccountInfo;
 use solana_program::entrypoint::ProgramResult;
+use solana_program::program::invoke_signed;
+use solana_program::program_error::ProgramError;
+use solana_program::program_pack::Pack;
 use solana_program::pubkey::Pubkey;
 
 
 pub fn process(
+    program_id: &Pubkey,
     accounts: &[AccountInfo],
 ) -> ProgramResult {
-    let mut accounts = &mut accounts.iter();
+    let accounts = &mut accounts.iter();
+
+    let admin = next_account(accounts, AccountSpec::new().signer().writable())?;
+    let exchange_booth = next_account(accounts, AccountSpec::new().writable().owner(program_id))?;
+    let vault_a = next_account(accounts, AccountSpec::new().writable().owner(&spl_token::ID))?;
+    let vault_b = next_account(accounts, AccountSpec::new().writable().owner(&spl_token::ID))?;
+    let dst_a = next_account(accounts, AccountSpec::new().writable().owner(&spl_token::ID))?;
+    let dst_b = next_account(accounts, AccountSpec::new().writable().owner(&spl_token::ID))?;
+    next_account(accounts, AccountSpec::new().address(&spl_token::ID))?;
+
+    let exchange_booth_data = *ExchangeBooth::load(exchange_booth)?;
+    if *admin.key != exchange_booth_data.admin {
+        return Err(ExchangeBoothError::IncorrectAdmin.into());
+    }
+    if *vault_a.key != exchange_booth_data.vault_a || *vault_b.key != exchange_booth_data.vault_b {
+        return Err(ExchangeBoothError::InvalidAccountAddress.into());
+    }
 
-    let admin = next_account_info(accounts)?;
-    let exchange_booth = next_account_info(accounts)?;
+    let amount_a = drain_and_close(program_id, exchange_booth, vault_a, exchange_booth_data.vault_a_bump, dst_a, admin)?;
+    let amount_b = drain_and_close(program_id, exchange_booth, vault_b, exchange_booth_data.vault_b_bump, dst_b, admin)?;
 
     **admin.try_borrow_mut_lamports()? = admin
         .lamports()
         .checked_add(exchange_booth.lamports())
         .ok_or(ExchangeBoothError::Overflow)?;
     **exchange_booth.try_borrow_mut_lamports()? = 0;
-    *exchange_booth.try_borrow_mut_data()? = &mut [];
+    exchange_booth.try_borrow_mut_data()?.fill(0);
+
+    ExchangeBoothEvent::Closed {
+        exchange_booth: *exchange_booth.key,
+        amount_a,
+        amount_b,
+    }.emit()?;
+
     Ok(())
-}
\ No newline at end of file
+}
+
+/// Sends the whole balance of `vault` to `dst`, then closes the vault and returns its rent to
+/// `admin`. Both CPIs are signed by the vault itself, which is its own token authority. Returns
+/// the amount drained.
+fn drain_and_close<'a>(
+    program_id: &Pubkey,
+    exchange_booth: &AccountInfo<'a>,
+    vault: &AccountInfo<'a>,
+    bump_seed: u8,
+    dst: &AccountInfo<'a>,
+    admin: &AccountInfo<'a>,
+) -> Result<u64, ProgramError> {
+    let vault_data = spl_token::state::Account::unpack(&vault.try_borrow_data()?)?;
+
+    let seeds: &[&[u8]] = &[exchange_booth.key.as_ref(), vault_data.mint.as_ref()];
+    check_program_address_with_bump(vault.key, seeds, bump_seed, program_id)?;
+    let bump_seed_array: &[&[u8]] = &[&[bump_seed]];
+    let seeds = [seeds, bump_seed_array].concat();
+
+    if vault_data.amount > 0 {
+        invoke_signed(
+            &spl_token::instruction::transfer(
+                &spl_token::id(),
+                vault.key,
+                dst.key,
+                vault.key,
+                &[],
+                vault_data.amount,
+            )?,
+            &[
+                vault.clone(),
+                dst.clone(),
+            ],
+            &[seeds.as_slice()],
+        )?;
+    }
+
+    invoke_signed(
+        &spl_token::instruction::close_account(
+            &spl_token::id(),
+            vault.key,
+            admin.key,
+            vault.key,
+            &[],
+        )?,
+        &[
+            vault.clone(),
+            admin.clone(),
+        ],
+        &[seeds.as_slice()],
+    )?;
+
+    Ok(vault_data.amount)
+}
@@ -1,30 +1,47 @@
-use solana_program::{msg};
-
 use crate::{
     error::ExchangeBoothError,
+    events::ExchangeBoothEvent,
     state::ExchangeBooth,
+    validation::{next_account, AccountSpec},
 };
 
-use borsh::{BorshDeserialize, BorshSerialize};
-use solana_program::account_info::{AccountInfo, next_account_info};
+use solana_program::account_info::AccountInfo;
 use solana_program::entrypoint::ProgramResult;
-use solana_program::program::{invoke, invoke_signed};
-use solana_program::program_error::ProgramError;
+use solana_program::program::invoke;
+use solana_program::program_pack::Pack;
 use solana_program::pubkey::Pubkey;
 
 
 pub fn process(
+    program_id: &Pubkey,
     accounts: &[AccountInfo],
     amount: u64,
 ) -> ProgramResult {
-    let mut accounts = &mut accounts.iter();
+    let accounts = &mut accounts.iter();
+
+    let src = next_account(accounts, AccountSpec::new().writable().owner(&spl_token::ID))?;
+    let dst = next_account(accounts, AccountSpec::new().writable().owner(&spl_token::ID))?;
+    let authority = next_account(accounts, AccountSpec::new().signer())?;
+    let exchange_booth = next_account(accounts, AccountSpec::new().writable().owner(program_id))?;
+    next_account(accounts, AccountSpec::new().address(&spl_token::ID))?;
+
+    let mut exchange_booth_data = ExchangeBooth::load_mut(exchange_booth)?;
 
-    let src = next_account_info(accounts)?;
-    let dst = next_account_info(accounts)?;
-    let authority = next_account_info(accounts)?;
+    if exchange_booth_data.is_paused() {
+        return Err(ExchangeBoothError::ExchangeBoothPaused.into());
+    }
+    if *authority.key != exchange_booth_data.admin && !exchange_booth_data.is_public_liquidity() {
+        return Err(ExchangeBoothError::IncorrectAdmin.into());
+    }
+    if *dst.key != exchange_booth_data.vault_a && *dst.key != exchange_booth_data.vault_b {
+        return Err(ExchangeBoothError::InvalidAccountAddress.into());
+    }
 
-    msg!("src {:?}", src);
-    msg!("dst {:?}", dst);
+    let src_mint = spl_token::state::Account::unpack(&src.try_borrow_data()?)?.mint;
+    let dst_mint = spl_token::state::Account::unpack(&dst.try_borrow_data()?)?.mint;
+    if src_mint != dst_mint {
+        return Err(ExchangeBoothError::MintMismatch.into());
+    }
 
     invoke(
         &spl_token::instruction::transfer(
@@ -42,5 +59,14 @@ pub fn process(
         ],
     )?;
 
+    exchange_booth_data.record_deposit(dst.key, amount)?;
+
+    ExchangeBoothEvent::Deposited {
+        exchange_booth: *exchange_booth.key,
+        vault: *dst.key,
+        authority: *authority.key,
+        amount,
+    }.emit()?;
+
     Ok(())
-}
\ No newline at end of file
+}
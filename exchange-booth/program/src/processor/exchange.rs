@@ -1,57 +1,118 @@
-use borsh::{BorshDeserialize, BorshSerialize};
-use solana_program::account_info::{AccountInfo, next_account_info};
+use solana_program::account_info::AccountInfo;
+use solana_program::clock::Clock;
 use solana_program::entrypoint::ProgramResult;
 use solana_program::msg;
-use solana_program::program::invoke_signed;
+use solana_program::program::{invoke, invoke_signed};
 use solana_program::program_error::ProgramError;
+use solana_program::program_pack::Pack;
 use solana_program::pubkey::Pubkey;
+use solana_program::sysvar::Sysvar;
+use spl_token::state::Mint;
 
 use crate::{
     error::ExchangeBoothError,
+    events::ExchangeBoothEvent,
     state::ExchangeBooth,
+    validation::{check_program_address_with_bump, next_account, AccountSpec},
 };
-use crate::processor::{deposit, withdraw};
+use crate::pricing::{self, Quote, Rate};
 use crate::state::Oracle;
 
+/// Which side of the trade the user fixed.
+pub enum Swap {
+    ExactIn { amount: u64, min_amount_out: u64 },
+    ExactOut { amount_out: u64, max_amount_in: u64 },
+}
+
 pub fn process(
     program_id: &Pubkey,
     accounts: &[AccountInfo],
     amount: u64,
+    min_amount_out: u64,
 ) -> ProgramResult {
-    let mut accounts = &mut accounts.iter();
+    swap(program_id, accounts, Swap::ExactIn { amount, min_amount_out })
+}
 
-    let deposit_src = next_account_info(accounts)?;
-    let deposit_dst = next_account_info(accounts)?;
-    let withdraw_src = next_account_info(accounts)?;
-    let withdraw_dst = next_account_info(accounts)?;
-    let withdraw_mint = next_account_info(accounts)?;
-    let authority = next_account_info(accounts)?;
-    let oracle = next_account_info(accounts)?;
+/// Prices `swap` against the booth's oracle and settles it: the user's deposit goes into one
+/// vault and the output is paid out of the other.
+pub fn swap(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    swap: Swap,
+) -> ProgramResult {
+    let accounts = &mut accounts.iter();
 
-    let exchange_booth = next_account_info(accounts)?;
-    let exchange_booth_data: ExchangeBooth = ExchangeBooth::try_from_slice(*exchange_booth.try_borrow_data()?)?;
+    let deposit_src = next_account(accounts, AccountSpec::new().writable().owner(&spl_token::ID))?;
+    let deposit_dst = next_account(accounts, AccountSpec::new().writable().owner(&spl_token::ID))?;
+    let withdraw_src = next_account(accounts, AccountSpec::new().writable().owner(&spl_token::ID))?;
+    let withdraw_dst = next_account(accounts, AccountSpec::new().writable().owner(&spl_token::ID))?;
+    let deposit_mint = next_account(accounts, AccountSpec::new().owner(&spl_token::ID))?;
+    let withdraw_mint = next_account(accounts, AccountSpec::new().owner(&spl_token::ID))?;
+    let authority = next_account(accounts, AccountSpec::new().signer())?;
+    let oracle = next_account(accounts, AccountSpec::new())?;
+    let exchange_booth = next_account(accounts, AccountSpec::new().writable().owner(program_id))?;
+    next_account(accounts, AccountSpec::new().address(&spl_token::ID))?;
 
-    if exchange_booth_data.oracle != *oracle.key {
-        return Err(ExchangeBoothError::UnknownOracle.into());
+    let mut exchange_booth_data = ExchangeBooth::load_mut(exchange_booth)?;
+    if exchange_booth_data.is_paused() {
+        return Err(ExchangeBoothError::ExchangeBoothPaused.into());
     }
 
-    let exchange_rate = Oracle::try_from_slice(&oracle.try_borrow_data()?[9..])?.exchange_rate;
-    msg!("Exchange rate is {:?}", exchange_rate);
-    deposit::process(&[deposit_src.clone(), deposit_dst.clone(), authority.clone()], amount)?;
+    let a_to_b = if *deposit_dst.key == exchange_booth_data.vault_a
+        && *withdraw_src.key == exchange_booth_data.vault_b
+    {
+        true
+    } else if *deposit_dst.key == exchange_booth_data.vault_b
+        && *withdraw_src.key == exchange_booth_data.vault_a
+    {
+        false
+    } else {
+        return Err(ExchangeBoothError::InvalidAccountAddress.into());
+    };
 
+    // the vaults were checked against their seeds at init, so the deposit mint only has to match
+    // its vault and just the paying vault is rederived for signing
+    let deposit_vault = spl_token::state::Account::unpack(&deposit_dst.try_borrow_data()?)?;
+    if deposit_vault.mint != *deposit_mint.key {
+        return Err(ExchangeBoothError::InvalidAccountAddress.into());
+    }
+    let withdraw_bump = if a_to_b {
+        exchange_booth_data.vault_b_bump
+    } else {
+        exchange_booth_data.vault_a_bump
+    };
     let seeds: &[&[u8]] = &[exchange_booth.key.as_ref(), withdraw_mint.key.as_ref()];
-    let (vault, bump_seed) = Pubkey::find_program_address(seeds, program_id);
-    let bump_seed_array: &[&[u8]] = &[&[bump_seed]];
+    check_program_address_with_bump(withdraw_src.key, seeds, withdraw_bump, program_id)?;
+    let bump_seed_array: &[&[u8]] = &[&[withdraw_bump]];
     let seeds = [seeds, bump_seed_array].concat();
 
+    let Price { amount_in: amount, amount_out, fee, .. } =
+        price(&exchange_booth_data, oracle, deposit_mint, withdraw_mint, a_to_b, swap)?;
+
+    invoke(
+        &spl_token::instruction::transfer(
+            &spl_token::id(),
+            deposit_src.key,
+            deposit_dst.key,
+            authority.key,
+            &[],
+            amount,
+        )?,
+        &[
+            deposit_src.clone(),
+            deposit_dst.clone(),
+            authority.clone(),
+        ],
+    )?;
+
     invoke_signed(
         &spl_token::instruction::transfer(
             &spl_token::id(),
             withdraw_src.key,
             withdraw_dst.key,
-            &vault,
+            withdraw_src.key,
             &[],
-            exchange_rate * amount,
+            amount_out,
         )?,
         &[
             withdraw_src.clone(),
@@ -60,5 +121,79 @@ pub fn process(
         &[seeds.as_slice()],
     )?;
 
+    exchange_booth_data.record_exchange(a_to_b, amount, fee)?;
+
+    ExchangeBoothEvent::Exchanged {
+        exchange_booth: *exchange_booth.key,
+        authority: *authority.key,
+        a_to_b,
+        amount_in: amount,
+        amount_out,
+        fee,
+    }.emit()?;
+
     Ok(())
-}
\ No newline at end of file
+}
+
+/// Result of pricing a swap.
+pub struct Price {
+    pub amount_in: u64,
+    pub amount_out: u64,
+    /// Kept by the booth, in base units of the withdraw mint.
+    pub fee: u64,
+    /// Oracle reading the swap was priced at.
+    pub oracle: Oracle,
+}
+
+/// Prices `swap` against the booth's oracle, which must be fresh, rescaling the rate by the
+/// decimals of the two mints and taking the booth's fee out of the output.
+pub fn price(
+    exchange_booth_data: &ExchangeBooth,
+    oracle: &AccountInfo,
+    deposit_mint: &AccountInfo,
+    withdraw_mint: &AccountInfo,
+    a_to_b: bool,
+    swap: Swap,
+) -> Result<Price, ProgramError> {
+    let oracle_data = Oracle::load(oracle, exchange_booth_data)?;
+
+    // a rate stamped after the current slot would never go stale, so it is refused as well
+    let current_slot = Clock::get()?.slot;
+    if oracle_data.last_update_slot > current_slot
+        || current_slot - oracle_data.last_update_slot > exchange_booth_data.max_staleness_slots
+    {
+        msg!("Oracle last updated at slot {:?}, current slot is {:?}", oracle_data.last_update_slot, current_slot);
+        return Err(ExchangeBoothError::StaleOracle.into());
+    }
+
+    let deposit_decimals = Mint::unpack(&deposit_mint.try_borrow_data()?)?.decimals;
+    let withdraw_decimals = Mint::unpack(&withdraw_mint.try_borrow_data()?)?.decimals;
+    // the oracle quotes mint_b per mint_a, so the B -> A leg uses the inverse rate
+    let rate = if a_to_b {
+        Rate::from_oracle(oracle_data.mantissa, oracle_data.exponent, deposit_decimals, withdraw_decimals)?
+    } else {
+        Rate::from_oracle(oracle_data.mantissa, oracle_data.exponent, withdraw_decimals, deposit_decimals)?
+            .inverse()
+    };
+
+    let Quote { amount_in, amount_out, fee } = match swap {
+        Swap::ExactIn { amount, min_amount_out } => {
+            let quote = pricing::quote_exact_in(rate, amount, exchange_booth_data.fee_bps)?;
+            if quote.amount_out < min_amount_out {
+                msg!("Exchange pays out {:?}, below the minimum of {:?}", quote.amount_out, min_amount_out);
+                return Err(ExchangeBoothError::SlippageExceeded.into());
+            }
+            quote
+        }
+        Swap::ExactOut { amount_out, max_amount_in } => {
+            let quote = pricing::quote_exact_out(rate, amount_out, exchange_booth_data.fee_bps)?;
+            if quote.amount_in > max_amount_in {
+                msg!("Exchange costs {:?}, above the maximum of {:?}", quote.amount_in, max_amount_in);
+                return Err(ExchangeBoothError::SlippageExceeded.into());
+            }
+            quote
+        }
+    };
+
+    Ok(Price { amount_in, amount_out, fee, oracle: oracle_data })
+}
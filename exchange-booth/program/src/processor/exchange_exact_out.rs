@@ -0,0 +1,15 @@
+use solana_program::account_info::AccountInfo;
+use solana_program::entrypoint::ProgramResult;
+use solana_program::pubkey::Pubkey;
+
+use crate::processor::exchange::{self, Swap};
+
+/// Same accounts as `Exchange`, but the user fixes `amount_out` and the input is derived from it.
+pub fn process(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    amount_out: u64,
+    max_amount_in: u64,
+) -> ProgramResult {
+    exchange::swap(program_id, accounts, Swap::ExactOut { amount_out, max_amount_in })
+}
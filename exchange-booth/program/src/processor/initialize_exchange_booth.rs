@@ -1,17 +1,16 @@
-use std::mem::size_of;
-use std::ops::DerefMut;
-use solana_program::{msg, system_instruction};
+use solana_program::{system_instruction, system_program, sysvar};
 
 use crate::{
     error::ExchangeBoothError,
+    events::ExchangeBoothEvent,
+    pricing,
     state::ExchangeBooth,
+    validation::{check_program_address, next_account, AccountSpec},
 };
 
-use borsh::{BorshDeserialize, BorshSerialize};
-use solana_program::account_info::{AccountInfo, next_account_info};
+use solana_program::account_info::AccountInfo;
 use solana_program::entrypoint::ProgramResult;
 use solana_program::program::invoke_signed;
-use solana_program::program_error::ProgramError;
 use solana_program::program_pack::Pack;
 use solana_program::pubkey::Pubkey;
 use solana_program::rent::Rent;
@@ -20,47 +19,51 @@ use solana_program::sysvar::Sysvar;
 pub fn process(
     program_id: &Pubkey,
     accounts: &[AccountInfo],
+    fee_bps: u16,
+    max_staleness_slots: u64,
+    oracle_program_id: Pubkey,
+    oracle_authority: Pubkey,
 ) -> ProgramResult {
     let accounts = &mut accounts.iter();
 
-    let admin = next_account_info(accounts)?;
-    msg!("ADMIN IS {:?}", admin.key);
-    let mint_a = next_account_info(accounts)?;
-    let mint_b = next_account_info(accounts)?;
-    let vault_a = next_account_info(accounts)?;
-    let vault_b = next_account_info(accounts)?;
-    let oracle = next_account_info(accounts)?;
-    let mut exchange_booth = next_account_info(accounts)?;
-    let _system_program = next_account_info(accounts)?;
-    let _spl_token = next_account_info(accounts)?;
-    let rent = next_account_info(accounts)?;
+    let admin = next_account(accounts, AccountSpec::new().signer().writable())?;
+    let mint_a = next_account(accounts, AccountSpec::new().owner(&spl_token::ID))?;
+    let mint_b = next_account(accounts, AccountSpec::new().owner(&spl_token::ID))?;
+    let vault_a = next_account(accounts, AccountSpec::new().writable())?;
+    let vault_b = next_account(accounts, AccountSpec::new().writable())?;
+    let oracle = next_account(accounts, AccountSpec::new())?;
+    let exchange_booth = next_account(accounts, AccountSpec::new().writable())?;
+    next_account(accounts, AccountSpec::new().address(&system_program::ID))?;
+    next_account(accounts, AccountSpec::new().address(&spl_token::ID))?;
+    let rent = next_account(accounts, AccountSpec::new().address(&sysvar::rent::ID))?;
 
-    if !admin.is_signer {
-        return Err(ProgramError::MissingRequiredSignature);
+    if !exchange_booth.data_is_empty() {
+        return Err(ExchangeBoothError::AccountAlreadyInitialized.into());
     }
 
-    let seeds: &[&[u8]] = &[b"exchange_booth", admin.key.as_ref(), mint_a.key.as_ref(), mint_b.key.as_ref(), oracle.key.as_ref()];
-    let (_pda, bump_seed) = Pubkey::find_program_address(seeds, program_id);
-    let bump_seed_array: &[&[u8]] = &[&[bump_seed]];
-    let seeds = [seeds, bump_seed_array].concat();
+    pricing::check_fee_bps(fee_bps)?;
 
-    msg!("{:?} {:?}", _pda, exchange_booth.key);
+    // the oracle is deliberately left out of the seeds so it can be rotated with UpdateOracle
+    let seeds: &[&[u8]] = &[b"exchange_booth", admin.key.as_ref(), mint_a.key.as_ref(), mint_b.key.as_ref()];
+    let bump = check_program_address(exchange_booth, seeds, program_id)?;
+    let bump_seed_array: &[&[u8]] = &[&[bump]];
+    let seeds = [seeds, bump_seed_array].concat();
 
     invoke_signed(
         &system_instruction::create_account(
             admin.key,
             exchange_booth.key,
-            Rent::get()?.minimum_balance(size_of::<ExchangeBooth>()),
-            size_of::<ExchangeBooth>() as u64,
+            Rent::get()?.minimum_balance(ExchangeBooth::LEN),
+            ExchangeBooth::LEN as u64,
             program_id,
         ),
         &[admin.clone(), exchange_booth.clone()],
         &[seeds.as_slice()],
-    );
+    )?;
 
     let seeds: &[&[u8]] = &[exchange_booth.key.as_ref(), mint_a.key.as_ref()];
-    let (_pda, bump_seed) = Pubkey::find_program_address(seeds, program_id);
-    let bump_seed_array: &[&[u8]] = &[&[bump_seed]];
+    let vault_a_bump = check_program_address(vault_a, seeds, program_id)?;
+    let bump_seed_array: &[&[u8]] = &[&[vault_a_bump]];
     let seeds = [seeds, bump_seed_array].concat();
 
     invoke_signed(
@@ -86,8 +89,8 @@ pub fn process(
     )?;
 
     let seeds: &[&[u8]] = &[exchange_booth.key.as_ref(), mint_b.key.as_ref()];
-    let (_pda, bump_seed) = Pubkey::find_program_address(seeds, program_id);
-    let bump_seed_array: &[&[u8]] = &[&[bump_seed]];
+    let vault_b_bump = check_program_address(vault_b, seeds, program_id)?;
+    let bump_seed_array: &[&[u8]] = &[&[vault_b_bump]];
     let seeds = [seeds, bump_seed_array].concat();
 
     invoke_signed(
@@ -112,13 +115,28 @@ pub fn process(
         &[seeds.as_slice()],
     )?;
 
-    ExchangeBooth {
-        is_initialized: true,
+    ExchangeBooth::new(
+        bump,
+        vault_a_bump,
+        vault_b_bump,
+        fee_bps,
+        *admin.key,
+        *vault_a.key,
+        *vault_b.key,
+        *oracle.key,
+        oracle_program_id,
+        oracle_authority,
+        max_staleness_slots,
+    ).save(exchange_booth)?;
+
+    ExchangeBoothEvent::BoothInitialized {
+        exchange_booth: *exchange_booth.key,
         admin: *admin.key,
         vault_a: *vault_a.key,
         vault_b: *vault_b.key,
         oracle: *oracle.key,
-    }.serialize(exchange_booth.try_borrow_mut_data()?.deref_mut())?;
+        fee_bps,
+    }.emit()?;
 
     Ok(())
 }
@@ -0,0 +1,91 @@
+use solana_program::{system_instruction, system_program};
+
+use crate::{
+    error::ExchangeBoothError,
+    events::ExchangeBoothEvent,
+    pricing,
+    state::{ExchangeBooth, VersionedExchangeBooth},
+    validation::{check_program_address, next_account, AccountSpec},
+};
+
+use solana_program::account_info::AccountInfo;
+use solana_program::entrypoint::ProgramResult;
+use solana_program::program::invoke;
+use solana_program::program_pack::Pack;
+use solana_program::pubkey::Pubkey;
+use solana_program::rent::Rent;
+use solana_program::sysvar::Sysvar;
+
+/// Rewrites a booth created before the version byte existed in the latest layout. The fields the
+/// old layout lacked are supplied by the admin, who also pays for the extra rent.
+pub fn process(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    fee_bps: u16,
+    max_staleness_slots: u64,
+    oracle_program_id: Pubkey,
+    oracle_authority: Pubkey,
+) -> ProgramResult {
+    let accounts = &mut accounts.iter();
+
+    let admin = next_account(accounts, AccountSpec::new().signer().writable())?;
+    let exchange_booth = next_account(accounts, AccountSpec::new().writable().owner(program_id))?;
+    let vault_a = next_account(accounts, AccountSpec::new().owner(&spl_token::ID))?;
+    let vault_b = next_account(accounts, AccountSpec::new().owner(&spl_token::ID))?;
+    let system_program = next_account(accounts, AccountSpec::new().address(&system_program::ID))?;
+
+    let legacy = match VersionedExchangeBooth::unpack(&exchange_booth.try_borrow_data()?)? {
+        VersionedExchangeBooth::V1(legacy) => legacy,
+        VersionedExchangeBooth::V2(_) => return Err(ExchangeBoothError::AccountAlreadyInitialized.into()),
+    };
+    if *admin.key != legacy.admin {
+        return Err(ExchangeBoothError::IncorrectAdmin.into());
+    }
+    if *vault_a.key != legacy.vault_a || *vault_b.key != legacy.vault_b {
+        return Err(ExchangeBoothError::InvalidAccountAddress.into());
+    }
+    pricing::check_fee_bps(fee_bps)?;
+
+    // old booths were derived with the oracle in their seeds; they keep their address
+    let mint_a = spl_token::state::Account::unpack(&vault_a.try_borrow_data()?)?.mint;
+    let mint_b = spl_token::state::Account::unpack(&vault_b.try_borrow_data()?)?.mint;
+    let bump = check_program_address(
+        exchange_booth,
+        &[b"exchange_booth", admin.key.as_ref(), mint_a.as_ref(), mint_b.as_ref(), legacy.oracle.as_ref()],
+        program_id,
+    )?;
+    let vault_a_bump = check_program_address(vault_a, &[exchange_booth.key.as_ref(), mint_a.as_ref()], program_id)?;
+    let vault_b_bump = check_program_address(vault_b, &[exchange_booth.key.as_ref(), mint_b.as_ref()], program_id)?;
+
+    let lamports = Rent::get()?
+        .minimum_balance(ExchangeBooth::LEN)
+        .saturating_sub(exchange_booth.lamports());
+    if lamports > 0 {
+        invoke(
+            &system_instruction::transfer(admin.key, exchange_booth.key, lamports),
+            &[admin.clone(), exchange_booth.clone(), system_program.clone()],
+        )?;
+    }
+    exchange_booth.realloc(ExchangeBooth::LEN, false)?;
+
+    ExchangeBooth::new(
+        bump,
+        vault_a_bump,
+        vault_b_bump,
+        fee_bps,
+        legacy.admin,
+        legacy.vault_a,
+        legacy.vault_b,
+        legacy.oracle,
+        oracle_program_id,
+        oracle_authority,
+        max_staleness_slots,
+    ).save(exchange_booth)?;
+
+    ExchangeBoothEvent::Migrated {
+        exchange_booth: *exchange_booth.key,
+        version: ExchangeBooth::VERSION,
+    }.emit()?;
+
+    Ok(())
+}
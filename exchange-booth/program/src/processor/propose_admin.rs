@@ -0,0 +1,36 @@
+use solana_program::account_info::AccountInfo;
+use solana_program::entrypoint::ProgramResult;
+use solana_program::pubkey::Pubkey;
+
+use crate::{
+    error::ExchangeBoothError,
+    events::ExchangeBoothEvent,
+    state::ExchangeBooth,
+    validation::{next_account, AccountSpec},
+};
+
+/// First half of an admin transfer: nothing changes hands until `new_admin` sends AcceptAdmin.
+pub fn process(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    new_admin: Pubkey,
+) -> ProgramResult {
+    let accounts = &mut accounts.iter();
+
+    let admin = next_account(accounts, AccountSpec::new().signer())?;
+    let exchange_booth = next_account(accounts, AccountSpec::new().writable().owner(program_id))?;
+
+    let mut exchange_booth_data = ExchangeBooth::load_mut(exchange_booth)?;
+    if *admin.key != exchange_booth_data.admin {
+        return Err(ExchangeBoothError::IncorrectAdmin.into());
+    }
+
+    exchange_booth_data.pending_admin = new_admin;
+
+    ExchangeBoothEvent::AdminProposed {
+        exchange_booth: *exchange_booth.key,
+        pending_admin: new_admin,
+    }.emit()?;
+
+    Ok(())
+}
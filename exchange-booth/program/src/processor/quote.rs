@@ -0,0 +1,71 @@
+use borsh::BorshSerialize;
+use solana_program::account_info::AccountInfo;
+use solana_program::entrypoint::ProgramResult;
+use solana_program::program::set_return_data;
+use solana_program::pubkey::Pubkey;
+
+use crate::{
+    error::ExchangeBoothError,
+    instruction::{Direction, QuoteResult},
+    processor::exchange::{price, Price, Swap},
+    state::ExchangeBooth,
+    validation::{check_program_address_with_bump, next_account, AccountSpec},
+};
+
+/// Prices an `Exchange` of `amount` exactly as the exchange itself would, and hands the result
+/// back through return data instead of moving any tokens.
+pub fn process(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    amount: u64,
+    direction: Direction,
+) -> ProgramResult {
+    let accounts = &mut accounts.iter();
+
+    let exchange_booth = next_account(accounts, AccountSpec::new().owner(program_id))?;
+    let oracle = next_account(accounts, AccountSpec::new())?;
+    let mint_a = next_account(accounts, AccountSpec::new().owner(&spl_token::ID))?;
+    let mint_b = next_account(accounts, AccountSpec::new().owner(&spl_token::ID))?;
+
+    let exchange_booth_data = ExchangeBooth::load(exchange_booth)?;
+    if exchange_booth_data.is_paused() {
+        return Err(ExchangeBoothError::ExchangeBoothPaused.into());
+    }
+
+    // the mints are only trusted once they derive the booth's vaults
+    check_program_address_with_bump(
+        &exchange_booth_data.vault_a,
+        &[exchange_booth.key.as_ref(), mint_a.key.as_ref()],
+        exchange_booth_data.vault_a_bump,
+        program_id,
+    )?;
+    check_program_address_with_bump(
+        &exchange_booth_data.vault_b,
+        &[exchange_booth.key.as_ref(), mint_b.key.as_ref()],
+        exchange_booth_data.vault_b_bump,
+        program_id,
+    )?;
+
+    let (deposit_mint, withdraw_mint, a_to_b) = match direction {
+        Direction::AToB => (mint_a, mint_b, true),
+        Direction::BToA => (mint_b, mint_a, false),
+    };
+    let Price { amount_out, fee, oracle: oracle_data, .. } = price(
+        &exchange_booth_data,
+        oracle,
+        deposit_mint,
+        withdraw_mint,
+        a_to_b,
+        Swap::ExactIn { amount, min_amount_out: 0 },
+    )?;
+
+    set_return_data(&QuoteResult {
+        amount_out,
+        fee,
+        mantissa: oracle_data.mantissa,
+        exponent: oracle_data.exponent,
+        last_update_slot: oracle_data.last_update_slot,
+    }.try_to_vec()?);
+
+    Ok(())
+}
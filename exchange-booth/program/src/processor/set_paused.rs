@@ -0,0 +1,35 @@
+use solana_program::account_info::AccountInfo;
+use solana_program::entrypoint::ProgramResult;
+use solana_program::pubkey::Pubkey;
+
+use crate::{
+    error::ExchangeBoothError,
+    events::ExchangeBoothEvent,
+    state::ExchangeBooth,
+    validation::{next_account, AccountSpec},
+};
+
+pub fn process(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    paused: bool,
+) -> ProgramResult {
+    let accounts = &mut accounts.iter();
+
+    let admin = next_account(accounts, AccountSpec::new().signer())?;
+    let exchange_booth = next_account(accounts, AccountSpec::new().writable().owner(program_id))?;
+
+    let mut exchange_booth_data = ExchangeBooth::load_mut(exchange_booth)?;
+    if *admin.key != exchange_booth_data.admin {
+        return Err(ExchangeBoothError::IncorrectAdmin.into());
+    }
+
+    exchange_booth_data.set_paused(paused);
+
+    ExchangeBoothEvent::PausedSet {
+        exchange_booth: *exchange_booth.key,
+        paused,
+    }.emit()?;
+
+    Ok(())
+}
@@ -0,0 +1,41 @@
+use solana_program::account_info::AccountInfo;
+use solana_program::entrypoint::ProgramResult;
+use solana_program::pubkey::Pubkey;
+
+use crate::{
+    error::ExchangeBoothError,
+    events::ExchangeBoothEvent,
+    state::{ExchangeBooth, Oracle},
+    validation::{next_account, AccountSpec},
+};
+
+pub fn process(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    oracle_authority: Pubkey,
+) -> ProgramResult {
+    let accounts = &mut accounts.iter();
+
+    let admin = next_account(accounts, AccountSpec::new().signer())?;
+    let exchange_booth = next_account(accounts, AccountSpec::new().writable().owner(program_id))?;
+    let oracle = next_account(accounts, AccountSpec::new())?;
+
+    let mut exchange_booth_data = ExchangeBooth::load_mut(exchange_booth)?;
+    if *admin.key != exchange_booth_data.admin {
+        return Err(ExchangeBoothError::IncorrectAdmin.into());
+    }
+
+    exchange_booth_data.oracle = *oracle.key;
+    exchange_booth_data.oracle_authority = oracle_authority;
+
+    // make sure the booth can actually read the new feed before switching to it
+    Oracle::load(oracle, &exchange_booth_data)?;
+
+    ExchangeBoothEvent::OracleUpdated {
+        exchange_booth: *exchange_booth.key,
+        oracle: *oracle.key,
+        oracle_authority,
+    }.emit()?;
+
+    Ok(())
+}
@@ -2,14 +2,15 @@ use solana_program::{msg};
 
 use crate::{
     error::ExchangeBoothError,
+    events::ExchangeBoothEvent,
     state::ExchangeBooth,
+    validation::{next_account, AccountSpec},
 };
 
-use borsh::{BorshDeserialize, BorshSerialize};
-use solana_program::account_info::{AccountInfo, next_account_info};
+use solana_program::account_info::AccountInfo;
 use solana_program::entrypoint::ProgramResult;
-use solana_program::program::{invoke, invoke_signed};
-use solana_program::program_error::ProgramError;
+use solana_program::program::invoke_signed;
+use solana_program::program_pack::Pack;
 use solana_program::pubkey::Pubkey;
 
 
@@ -18,22 +19,35 @@ pub fn process(
     accounts: &[AccountInfo],
     amount: u64,
 ) -> ProgramResult {
-    let mut accounts = &mut accounts.iter();
+    let accounts = &mut accounts.iter();
 
-    let src = next_account_info(accounts)?;
-    let dst = next_account_info(accounts)?;
-    let mint = next_account_info(accounts)?;
-    let admin = next_account_info(accounts)?;
-    let exchange_booth = next_account_info(accounts)?;
-    let exchange_booth_data: ExchangeBooth = ExchangeBooth::try_from_slice(*exchange_booth.try_borrow_data()?)?;
+    let src = next_account(accounts, AccountSpec::new().writable().owner(&spl_token::ID))?;
+    let dst = next_account(accounts, AccountSpec::new().writable().owner(&spl_token::ID))?;
+    let mint = next_account(accounts, AccountSpec::new().owner(&spl_token::ID))?;
+    let admin = next_account(accounts, AccountSpec::new().signer())?;
+    let exchange_booth = next_account(accounts, AccountSpec::new().writable().owner(program_id))?;
+    next_account(accounts, AccountSpec::new().address(&spl_token::ID))?;
+
+    let mut exchange_booth_data = ExchangeBooth::load_mut(exchange_booth)?;
 
     if *admin.key != exchange_booth_data.admin {
-        msg!("{:?} {:?} FAILING", admin.key, exchange_booth_data.admin);
-        return Err(ProgramError::InvalidAccountData);
+        return Err(ExchangeBoothError::IncorrectAdmin.into());
+    }
+
+    // src must be one of the booth's vaults, which were checked against their seeds at init
+    let bump_seed = exchange_booth_data
+        .vault_bump(src.key)
+        .ok_or(ExchangeBoothError::InvalidAccountAddress)?;
+    let vault = spl_token::state::Account::unpack(&src.try_borrow_data()?)?;
+    if vault.mint != *mint.key {
+        return Err(ExchangeBoothError::MintMismatch.into());
+    }
+    if amount > vault.amount {
+        msg!("Vault holds {:?}, cannot withdraw {:?}", vault.amount, amount);
+        return Err(ExchangeBoothError::InsufficientVaultBalance.into());
     }
 
     let seeds: &[&[u8]] = &[exchange_booth.key.as_ref(), mint.key.as_ref()];
-    let (_pda, bump_seed) = Pubkey::find_program_address(seeds, program_id);
     let bump_seed_array: &[&[u8]] = &[&[bump_seed]];
     let seeds = [seeds, bump_seed_array].concat();
 
@@ -53,5 +67,13 @@ pub fn process(
         &[seeds.as_slice()],
     )?;
 
+    exchange_booth_data.record_withdrawal(src.key, amount)?;
+
+    ExchangeBoothEvent::Withdrew {
+        exchange_booth: *exchange_booth.key,
+        vault: *src.key,
+        amount,
+    }.emit()?;
+
     Ok(())
 }
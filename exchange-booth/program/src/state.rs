@@ -1,18 +1,346 @@
-use std::mem::size_of;
+
+use std::cell::{Ref, RefMut};
 
 use borsh::{BorshDeserialize, BorshSerialize};
+use bytemuck::{Pod, Zeroable};
+use solana_program::account_info::AccountInfo;
+use solana_program::borsh::try_from_slice_unchecked;
+use solana_program::entrypoint::ProgramResult;
+use solana_program::program_error::ProgramError;
 use solana_program::pubkey::Pubkey;
 
+use crate::error::ExchangeBoothError;
+
+/// Exchange rate published by the oracle: `mantissa * 10^exponent` mint_b per mint_a.
 #[derive(BorshSerialize, BorshDeserialize, Debug, Clone)]
 pub struct Oracle {
-    pub exchange_rate: u64,
+    pub mantissa: u64,
+    pub exponent: i32,
+    /// Slot at which the publisher last wrote the rate.
+    pub last_update_slot: u64,
 }
 
-#[derive(BorshSerialize, BorshDeserialize, Debug, Clone)]
+impl Oracle {
+    /// Authorized echo buffers start with a bump seed and an 8 byte buffer seed.
+    pub const HEADER_LEN: usize = 9;
+    /// Encoded size of the rate that follows the header.
+    pub const LEN: usize = 8 + 4 + 8;
+
+    /// Reads the rate out of the booth's oracle, after checking that the account is an
+    /// authorized echo buffer owned by the trusted oracle program and written by the booth's
+    /// oracle authority.
+    pub fn load(oracle: &AccountInfo, exchange_booth: &ExchangeBooth) -> Result<Oracle, ProgramError> {
+        if exchange_booth.oracle != *oracle.key {
+            return Err(ExchangeBoothError::UnknownOracle.into());
+        }
+        if exchange_booth.oracle_program_id != *oracle.owner {
+            return Err(ExchangeBoothError::InvalidAccountOwner.into());
+        }
+
+        let data = oracle.try_borrow_data()?;
+        if data.len() < Self::HEADER_LEN {
+            return Err(ExchangeBoothError::OracleDataTooShort.into());
+        }
+        let seeds: &[&[u8]] = &[
+            b"authority",
+            exchange_booth.oracle_authority.as_ref(),
+            &data[1..Self::HEADER_LEN],
+            &data[..1],
+        ];
+        let pda = Pubkey::create_program_address(seeds, &exchange_booth.oracle_program_id)
+            .map_err(|_| ExchangeBoothError::InvalidOracleHeader)?;
+        if pda != *oracle.key {
+            return Err(ExchangeBoothError::InvalidOracleHeader.into());
+        }
+
+        Oracle::deserialize(&mut &data[Self::HEADER_LEN..])
+            .map_err(|_| ExchangeBoothError::OracleDataTooShort.into())
+    }
+}
+
+/// Booth state. Fields are ordered so that the encoding has no gaps: the byte-sized fields and
+/// `fee_bps` fill the first 8 bytes, then the keys, then `max_staleness_slots` on an 8 byte
+/// boundary, then the settings, the running totals and `reserved` for fields added later.
+///
+/// Without gaps the Borsh encoding and the `repr(C)` layout are the same bytes, so instructions
+/// read and write the booth in place with [`ExchangeBooth::load`] and
+/// [`ExchangeBooth::load_mut`] while clients can still decode it with Borsh. Flags are stored as
+/// `u8` since `bool` is not `Pod`.
+#[repr(C)]
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, Copy, Pod, Zeroable)]
 pub struct ExchangeBooth {
+    /// Layout version, see [`ExchangeBooth::VERSION`].
+    pub version: u8,
+    pub is_initialized: u8,
+    /// Set by the admin to halt deposits and exchanges; withdrawals still go through.
+    pub paused: u8,
+    /// Bump seed of the booth's own program address.
+    pub bump: u8,
+    /// Bump seeds of the vaults, recorded at init so signing never has to search for them.
+    pub vault_a_bump: u8,
+    pub vault_b_bump: u8,
+    /// Fee charged on the output of every exchange, in basis points.
+    pub fee_bps: u16,
+    pub admin: Pubkey,
+    /// Admin proposed by the current admin, `Pubkey::default()` when no transfer is pending.
+    pub pending_admin: Pubkey,
+    pub vault_a: Pubkey,
+    pub vault_b: Pubkey,
+    pub oracle: Pubkey,
+    /// Program that owns `oracle`, normally the echo program.
+    pub oracle_program_id: Pubkey,
+    /// Authority whose authorized echo buffer `oracle` is.
+    pub oracle_authority: Pubkey,
+    /// Oldest oracle update, in slots, that the booth will still trade on.
+    pub max_staleness_slots: u64,
+    /// Set by the admin to let anyone deposit into the vaults, not just the admin.
+    pub public_liquidity: u8,
+    pub padding: [u8; 7],
+    /// Running totals for reconciliation, all in base units of the mint named by the suffix.
+    pub total_deposited_a: u64,
+    pub total_deposited_b: u64,
+    pub total_withdrawn_a: u64,
+    pub total_withdrawn_b: u64,
+    /// Exchange volume by direction, counted on the input side.
+    pub volume_a_to_b: u64,
+    pub volume_b_to_a: u64,
+    /// Fees kept by the booth, which are taken out of the output side.
+    pub fees_collected_a: u64,
+    pub fees_collected_b: u64,
+    pub trade_count: u64,
+    /// Zeroed space for future fields, so they can be added without growing the account.
+    pub reserved: [u64; 22],
+}
+
+impl ExchangeBooth {
+    /// Current layout version. Booths written before the version byte existed start with
+    /// `is_initialized`, which reads as version 1.
+    pub const VERSION: u8 = 2;
+    /// Encoded size of the booth, which is also the size of its account.
+    pub const LEN: usize = 8 + 7 * 32 + 8 + 8 + 9 * 8 + 22 * 8;
+
+    /// A booth in the latest layout, as init and migrate write it: unpaused, with no pending
+    /// admin, public liquidity off and all totals at zero.
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        bump: u8,
+        vault_a_bump: u8,
+        vault_b_bump: u8,
+        fee_bps: u16,
+        admin: Pubkey,
+        vault_a: Pubkey,
+        vault_b: Pubkey,
+        oracle: Pubkey,
+        oracle_program_id: Pubkey,
+        oracle_authority: Pubkey,
+        max_staleness_slots: u64,
+    ) -> ExchangeBooth {
+        ExchangeBooth {
+            version: Self::VERSION,
+            is_initialized: 1,
+            bump,
+            vault_a_bump,
+            vault_b_bump,
+            fee_bps,
+            admin,
+            pending_admin: Pubkey::default(),
+            vault_a,
+            vault_b,
+            oracle,
+            oracle_program_id,
+            oracle_authority,
+            max_staleness_slots,
+            ..ExchangeBooth::zeroed()
+        }
+    }
+
+    /// Borrows the booth in place out of `exchange_booth`, which must already be initialized and
+    /// in the latest layout.
+    pub fn load<'a>(exchange_booth: &'a AccountInfo) -> Result<Ref<'a, ExchangeBooth>, ProgramError> {
+        let data = exchange_booth.try_borrow_data()?;
+        Self::check(&data)?;
+        Ok(Ref::map(data, |data| bytemuck::from_bytes(&data[..Self::LEN])))
+    }
+
+    /// Like [`ExchangeBooth::load`], but changes to the booth are written straight to the account.
+    pub fn load_mut<'a>(exchange_booth: &'a AccountInfo) -> Result<RefMut<'a, ExchangeBooth>, ProgramError> {
+        let data = exchange_booth.try_borrow_mut_data()?;
+        Self::check(&data)?;
+        Ok(RefMut::map(data, |data| bytemuck::from_bytes_mut(&mut data[..Self::LEN])))
+    }
+
+    fn check(data: &[u8]) -> ProgramResult {
+        match data.first() {
+            Some(&Self::VERSION) => {}
+            Some(&ExchangeBoothV1::VERSION) => return Err(ExchangeBoothError::OutdatedExchangeBooth.into()),
+            None | Some(0) => return Err(ExchangeBoothError::AccountNotInitialized.into()),
+            Some(_) => return Err(ExchangeBoothError::UnsupportedVersion.into()),
+        }
+        let exchange_booth_data: &ExchangeBooth = data
+            .get(..Self::LEN)
+            .and_then(|data| bytemuck::try_from_bytes(data).ok())
+            .ok_or(ExchangeBoothError::InvalidAccountData)?;
+        if !exchange_booth_data.is_initialized() {
+            return Err(ExchangeBoothError::AccountNotInitialized.into());
+        }
+        Ok(())
+    }
+
+    pub fn is_initialized(&self) -> bool {
+        self.is_initialized != 0
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.paused != 0
+    }
+
+    pub fn set_paused(&mut self, paused: bool) {
+        self.paused = paused as u8;
+    }
+
+    pub fn is_public_liquidity(&self) -> bool {
+        self.public_liquidity != 0
+    }
+
+    pub fn set_public_liquidity(&mut self, enabled: bool) {
+        self.public_liquidity = enabled as u8;
+    }
+
+    /// Adds a deposit of `amount` into `vault` to the totals.
+    pub fn record_deposit(&mut self, vault: &Pubkey, amount: u64) -> ProgramResult {
+        let total = if *vault == self.vault_a {
+            &mut self.total_deposited_a
+        } else {
+            &mut self.total_deposited_b
+        };
+        checked_add(total, amount)
+    }
+
+    /// Adds a withdrawal of `amount` out of `vault` to the totals.
+    pub fn record_withdrawal(&mut self, vault: &Pubkey, amount: u64) -> ProgramResult {
+        let total = if *vault == self.vault_a {
+            &mut self.total_withdrawn_a
+        } else {
+            &mut self.total_withdrawn_b
+        };
+        checked_add(total, amount)
+    }
+
+    /// Adds an exchange of `amount_in` that paid `fee` to the booth to the totals.
+    pub fn record_exchange(&mut self, a_to_b: bool, amount_in: u64, fee: u64) -> ProgramResult {
+        if a_to_b {
+            checked_add(&mut self.volume_a_to_b, amount_in)?;
+            checked_add(&mut self.fees_collected_b, fee)?;
+        } else {
+            checked_add(&mut self.volume_b_to_a, amount_in)?;
+            checked_add(&mut self.fees_collected_a, fee)?;
+        }
+        checked_add(&mut self.trade_count, 1)
+    }
+
+    /// Bump seed of `vault` if it is one of the booth's vaults.
+    pub fn vault_bump(&self, vault: &Pubkey) -> Option<u8> {
+        if *vault == self.vault_a {
+            Some(self.vault_a_bump)
+        } else if *vault == self.vault_b {
+            Some(self.vault_b_bump)
+        } else {
+            None
+        }
+    }
+
+    /// Writes the whole booth to `exchange_booth`, for when it is first created or rewritten.
+    pub fn save(&self, exchange_booth: &AccountInfo) -> ProgramResult {
+        exchange_booth
+            .try_borrow_mut_data()?
+            .get_mut(..Self::LEN)
+            .ok_or(ExchangeBoothError::InvalidAccountData)?
+            .copy_from_slice(bytemuck::bytes_of(self));
+        Ok(())
+    }
+}
+
+fn checked_add(total: &mut u64, amount: u64) -> ProgramResult {
+    *total = total.checked_add(amount).ok_or(ExchangeBoothError::Overflow)?;
+    Ok(())
+}
+
+/// Booth layout written before the version byte existed, kept so old booths can be migrated.
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone)]
+pub struct ExchangeBoothV1 {
     pub is_initialized: bool,
     pub admin: Pubkey,
     pub vault_a: Pubkey,
     pub vault_b: Pubkey,
     pub oracle: Pubkey,
-}
\ No newline at end of file
+}
+
+impl ExchangeBoothV1 {
+    pub const VERSION: u8 = 1;
+    pub const LEN: usize = 1 + 4 * 32;
+}
+
+/// A booth in any layout the program has ever written, told apart by the leading version byte.
+#[derive(Debug, Clone)]
+pub enum VersionedExchangeBooth {
+    V1(ExchangeBoothV1),
+    V2(Box<ExchangeBooth>),
+}
+
+impl VersionedExchangeBooth {
+    pub fn unpack(data: &[u8]) -> Result<VersionedExchangeBooth, ProgramError> {
+        match data.first() {
+            // an uninitialized booth, whatever its layout
+            None | Some(0) => Err(ExchangeBoothError::AccountNotInitialized.into()),
+            Some(&ExchangeBoothV1::VERSION) => Ok(VersionedExchangeBooth::V1(try_from_slice_unchecked(data)?)),
+            Some(&ExchangeBooth::VERSION) => Ok(VersionedExchangeBooth::V2(Box::new(try_from_slice_unchecked(data)?))),
+            Some(_) => Err(ExchangeBoothError::UnsupportedVersion.into()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_state_len() -> std::io::Result<()> {
+        // every state struct must encode to exactly the space allocated for it
+        let exchange_booth_data = ExchangeBooth {
+            version: ExchangeBooth::VERSION,
+            is_initialized: 1,
+            paused: 0,
+            bump: 255,
+            vault_a_bump: 255,
+            vault_b_bump: 255,
+            fee_bps: crate::pricing::BPS - 1,
+            admin: Pubkey::new_unique(),
+            pending_admin: Pubkey::new_unique(),
+            vault_a: Pubkey::new_unique(),
+            vault_b: Pubkey::new_unique(),
+            oracle: Pubkey::new_unique(),
+            oracle_program_id: Pubkey::new_unique(),
+            oracle_authority: Pubkey::new_unique(),
+            max_staleness_slots: u64::MAX,
+            public_liquidity: 1,
+            ..ExchangeBooth::zeroed()
+        };
+        assert_eq!(exchange_booth_data.try_to_vec()?.len(), ExchangeBooth::LEN);
+        // instructions read the booth in place, so its Borsh encoding must be its in-memory layout
+        assert_eq!(exchange_booth_data.try_to_vec()?, bytemuck::bytes_of(&exchange_booth_data));
+
+        // booths created before the version byte were allocated exactly this much
+        let legacy_data = ExchangeBoothV1 {
+            is_initialized: true,
+            admin: Pubkey::new_unique(),
+            vault_a: Pubkey::new_unique(),
+            vault_b: Pubkey::new_unique(),
+            oracle: Pubkey::new_unique(),
+        };
+        assert_eq!(legacy_data.try_to_vec()?.len(), ExchangeBoothV1::LEN);
+
+        let oracle_data = Oracle { mantissa: u64::MAX, exponent: i32::MIN, last_update_slot: u64::MAX };
+        assert_eq!(oracle_data.try_to_vec()?.len(), Oracle::LEN);
+        Ok(())
+    }
+}
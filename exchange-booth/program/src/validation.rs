@@ -0,0 +1,114 @@
+use std::slice::Iter;
+
+use solana_program::account_info::{AccountInfo, next_account_info};
+use solana_program::entrypoint::ProgramResult;
+use solana_program::msg;
+use solana_program::program_error::ProgramError;
+use solana_program::pubkey::Pubkey;
+
+use crate::error::ExchangeBoothError;
+
+/// What an instruction expects of one of its accounts.
+///
+/// Processors pull every account through [`next_account`] with a spec, e.g.
+/// `AccountSpec::new().signer().writable()` for a fee payer, so the checks read like the account
+/// table of the instruction.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct AccountSpec<'a> {
+    signer: bool,
+    writable: bool,
+    owner: Option<&'a Pubkey>,
+    address: Option<&'a Pubkey>,
+}
+
+impl<'a> AccountSpec<'a> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn signer(mut self) -> Self {
+        self.signer = true;
+        self
+    }
+
+    pub fn writable(mut self) -> Self {
+        self.writable = true;
+        self
+    }
+
+    pub fn owner(mut self, owner: &'a Pubkey) -> Self {
+        self.owner = Some(owner);
+        self
+    }
+
+    pub fn address(mut self, address: &'a Pubkey) -> Self {
+        self.address = Some(address);
+        self
+    }
+
+    pub fn check(&self, account: &AccountInfo) -> ProgramResult {
+        if self.signer && !account.is_signer {
+            msg!("{:?} must sign", account.key);
+            return Err(ExchangeBoothError::MissingRequiredSignature.into());
+        }
+        if self.writable && !account.is_writable {
+            msg!("{:?} must be writable", account.key);
+            return Err(ExchangeBoothError::AccountMustBeWritable.into());
+        }
+        if let Some(owner) = self.owner {
+            if account.owner != owner {
+                msg!("{:?} must be owned by {:?}", account.key, owner);
+                return Err(ExchangeBoothError::InvalidAccountOwner.into());
+            }
+        }
+        if let Some(address) = self.address {
+            if account.key != address {
+                msg!("{:?} must be {:?}", account.key, address);
+                return Err(ExchangeBoothError::InvalidAccountAddress.into());
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Takes the next account off `accounts` and checks it against `spec`.
+pub fn next_account<'a, 'b>(
+    accounts: &mut Iter<'a, AccountInfo<'b>>,
+    spec: AccountSpec,
+) -> Result<&'a AccountInfo<'b>, ProgramError> {
+    let account = next_account_info(accounts)?;
+    spec.check(account)?;
+    Ok(account)
+}
+
+/// Checks that `account` is the program address derived from `seeds` and returns its bump seed.
+pub fn check_program_address(
+    account: &AccountInfo,
+    seeds: &[&[u8]],
+    program_id: &Pubkey,
+) -> Result<u8, ProgramError> {
+    let (pda, bump_seed) = Pubkey::find_program_address(seeds, program_id);
+    if pda != *account.key {
+        msg!("{:?} is not the program address {:?}", account.key, pda);
+        return Err(ExchangeBoothError::InvalidProgramAddress.into());
+    }
+    Ok(bump_seed)
+}
+
+/// Checks that `address` is the program address of `seeds` with a bump seed that was recorded
+/// earlier, which is much cheaper than searching for it again with [`check_program_address`].
+pub fn check_program_address_with_bump(
+    address: &Pubkey,
+    seeds: &[&[u8]],
+    bump_seed: u8,
+    program_id: &Pubkey,
+) -> ProgramResult {
+    let bump_seed_array: &[&[u8]] = &[&[bump_seed]];
+    let pda = Pubkey::create_program_address(&[seeds, bump_seed_array].concat(), program_id)
+        .map_err(|_| ExchangeBoothError::InvalidProgramAddress)?;
+    if pda != *address {
+        msg!("{:?} is not the program address {:?}", address, pda);
+        return Err(ExchangeBoothError::InvalidProgramAddress.into());
+    }
+    Ok(())
+}
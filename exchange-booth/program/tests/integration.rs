@@ -8,7 +8,8 @@ use assert_matches::*;
 use borsh::{BorshDeserialize, BorshSerialize};
 use solana_client::rpc_client::RpcClient;
 use solana_sdk::{signature::Signer, transaction::Transaction};
-use solana_sdk::account::ReadableAccount;
+use solana_sdk::account::{AccountSharedData, ReadableAccount};
+use solana_sdk::borsh::try_from_slice_unchecked;
 use solana_sdk::instruction::AccountMeta;
 use solana_sdk::instruction::Instruction;
 use solana_sdk::message::Message;
@@ -19,21 +20,84 @@ use solana_sdk::rent::Rent;
 use solana_sdk::signature::Keypair;
 use solana_sdk::system_instruction;
 use solana_sdk::system_program;
-use solana_sdk::sysvar;
+use solana_sdk::transaction::TransactionError;
 use solana_validator::test_validator::*;
 use spl_token::instruction::initialize_mint;
 use spl_token::state::{Account, Mint};
-use echo::instruction::EchoInstruction;
-use exchange_booth::instruction::ExchangeBoothInstruction;
-use exchange_booth::state::{ExchangeBooth, Oracle};
+use echo::pda::find_authorized_buffer_address;
+use exchange_booth::error::ExchangeBoothError;
+use exchange_booth::events::{decode_events, ExchangeBoothEvent};
+use exchange_booth::instruction::{self, Direction, ExchangeBoothInstruction, QuoteResult};
+use exchange_booth::pda::{find_exchange_booth_address, find_legacy_exchange_booth_address, find_vault_address};
+use exchange_booth::pricing;
+use exchange_booth::state::{ExchangeBooth, ExchangeBoothV1, Oracle};
+
+/// Every program address a program derives costs 1,500 compute units, so finding a vault whose
+/// bump seed is 250 or lower with `find_program_address` costs at least 7,500 more than finding
+/// one whose bump is 255. Withdrawals and exchanges sign with the bumps stored at init, so paying
+/// out of either vault must cost the same to within this many compute units.
+const BUMP_SEARCH_TOLERANCE: u64 = 500;
+
+/// Simulates `transaction` and returns its log messages.
+fn simulate_logs(rpc_client: &RpcClient, transaction: &Transaction) -> anyhow::Result<Vec<String>> {
+    let result = rpc_client.simulate_transaction(transaction)?.value;
+    if let Some(err) = result.err {
+        return Err(anyhow!("simulation failed: {:?}", err));
+    }
+    Ok(result.logs.unwrap_or_default())
+}
+
+/// Simulates `transaction` and reads back how many compute units `program_id` consumed.
+fn compute_units_consumed(rpc_client: &RpcClient, transaction: &Transaction, program_id: &Pubkey) -> anyhow::Result<u64> {
+    let prefix = format!("Program {} consumed ", program_id);
+    simulate_logs(rpc_client, transaction)?
+        .iter()
+        .find_map(|log| log.strip_prefix(&prefix)?.split(' ').next()?.parse().ok())
+        .ok_or_else(|| anyhow!("no compute units logged for {}", program_id))
+}
+
+/// Sends `transaction` and checks that it fails with `error`.
+fn assert_exchange_booth_error(rpc_client: &RpcClient, transaction: &Transaction, error: ExchangeBoothError) {
+    let result = rpc_client.send_and_confirm_transaction(transaction);
+    let program_error = match result.as_ref().map_err(|err| err.get_transaction_error()) {
+        Err(Some(TransactionError::InstructionError(_, instruction_error))) => ProgramError::try_from(instruction_error).ok(),
+        _ => None,
+    };
+    assert_eq!(program_error, Some(ProgramError::Custom(error as u32)), "{:?}", result);
+}
+
+/// A rent-exempt account holding `data`, for preloading state into the test validator.
+fn rent_exempt_account(data: Vec<u8>, owner: &Pubkey) -> AccountSharedData {
+    let mut account = AccountSharedData::new(Rent::default().minimum_balance(data.len()), data.len(), owner);
+    account.set_data(data);
+    account
+}
+
+/// An initialized mint with no decimals.
+fn mint_account() -> anyhow::Result<AccountSharedData> {
+    let mut data = vec![0; Mint::LEN];
+    Mint::pack(Mint { decimals: 0, is_initialized: true, ..Mint::default() }, &mut data)?;
+    Ok(rent_exempt_account(data, &spl_token::id()))
+}
+
+/// An initialized token account of `mint` holding `amount`.
+fn token_account(mint: Pubkey, owner: Pubkey, amount: u64) -> anyhow::Result<AccountSharedData> {
+    let mut data = vec![0; Account::LEN];
+    Account::pack(Account {
+        mint,
+        owner,
+        amount,
+        state: spl_token::state::AccountState::Initialized,
+        ..Account::default()
+    }, &mut data)?;
+    Ok(rent_exempt_account(data, &spl_token::id()))
+}
 
 #[test]
 fn test_exchange_booth() -> anyhow::Result<()> {
     solana_logger::setup_with_default("solana_program_runtime=debug");
     let exchange_booth_program_id = Pubkey::new_unique();
     let echo_program_id = Pubkey::new_unique();
-    let mint_a = Keypair::new();
-    let mint_b = Keypair::new();
     let user = Keypair::new();
     let admin_wallet_a = Keypair::new();
     let admin_wallet_b = Keypair::new();
@@ -48,45 +112,38 @@ fn test_exchange_booth() -> anyhow::Result<()> {
     // rpc_client.request_airdrop(&user.pubkey(), 1_000_000_000)?;
     // let rpc_client = RpcClient::new_with_commitment("https://api.devnet.solana.com".to_string(), CommitmentLevel::confirmed());
 
-    let buffer_seed = 42u64;
-    let (oracle, _) = Pubkey::find_program_address(
-        &[
-            b"authority",
-            admin.pubkey().as_ref(),
-            &buffer_seed.to_le_bytes(),
-        ],
-        &echo_program_id,
-    );
-
-    let (exchange_booth, _) = Pubkey::find_program_address(
-        &[
-            b"exchange_booth",
-            admin.pubkey().as_ref(),
-            mint_a.pubkey().as_ref(),
-            mint_b.pubkey().as_ref(),
-            oracle.as_ref()
-        ],
-        &exchange_booth_program_id,
-    );
+    // pick mints whose vault_a bump is at least five below 255 and whose vault_b bump is 255, so
+    // that searching for vault_a's bump would show up as a difference in compute units
+    let (mint_a, mint_b) = loop {
+        let mint_a = Keypair::new();
+        let mint_b = Keypair::new();
+        let (exchange_booth, _) = find_exchange_booth_address(
+            &exchange_booth_program_id,
+            &admin.pubkey(),
+            &mint_a.pubkey(),
+            &mint_b.pubkey(),
+        );
+        let (_, vault_a_bump) = find_vault_address(&exchange_booth_program_id, &exchange_booth, &mint_a.pubkey());
+        let (_, vault_b_bump) = find_vault_address(&exchange_booth_program_id, &exchange_booth, &mint_b.pubkey());
+        if vault_a_bump <= 250 && vault_b_bump == 255 {
+            break (mint_a, mint_b);
+        }
+    };
 
-    let (vault_a, _) = Pubkey::find_program_address(
-        &[
-            exchange_booth.as_ref(),
-            mint_a.pubkey().as_ref(),
-        ],
-        &exchange_booth_program_id,
-    );
+    let buffer_seed = 42u64;
+    let (oracle, _) = find_authorized_buffer_address(&echo_program_id, &admin.pubkey(), buffer_seed);
 
-    let (vault_b, _) = Pubkey::find_program_address(
-        &[
-            exchange_booth.as_ref(),
-            mint_b.pubkey().as_ref(),
-        ],
+    let (exchange_booth, exchange_booth_bump) = find_exchange_booth_address(
         &exchange_booth_program_id,
+        &admin.pubkey(),
+        &mint_a.pubkey(),
+        &mint_b.pubkey(),
     );
+    let (vault_a, vault_a_bump) = find_vault_address(&exchange_booth_program_id, &exchange_booth, &mint_a.pubkey());
+    let (vault_b, vault_b_bump) = find_vault_address(&exchange_booth_program_id, &exchange_booth, &mint_b.pubkey());
 
     let blockhash = rpc_client.get_latest_blockhash().unwrap();
-    let mut init_tx = Transaction::new_signed_with_payer(
+    let mut mint_tx = Transaction::new_signed_with_payer(
         &[
             // MINTS
             system_instruction::create_account(
@@ -119,29 +176,83 @@ fn test_exchange_booth() -> anyhow::Result<()> {
                 None,
                 0,
             )?,
-            Instruction {
-                program_id: exchange_booth_program_id,
-                accounts: vec![
-                    AccountMeta::new_readonly(admin.pubkey(), true),
-                    AccountMeta::new_readonly(mint_a.pubkey(), false),
-                    AccountMeta::new_readonly(mint_b.pubkey(), false),
-                    AccountMeta::new(vault_a, false),
-                    AccountMeta::new(vault_b, false),
-                    AccountMeta::new_readonly(oracle, false),
-                    AccountMeta::new(exchange_booth, false),
-                    AccountMeta::new_readonly(system_program::id(), false),
-                    AccountMeta::new_readonly(spl_token::id(), false),
-                    AccountMeta::new_readonly(sysvar::rent::id(), false),
-                ],
-                data: ExchangeBoothInstruction::InitializeExchangeBooth.try_to_vec()?,
-            },
         ],
         Some(&admin.pubkey()),
         &vec![&admin, &mint_a, &mint_b],
         blockhash,
     );
+    mint_tx.sign(&vec![&admin, &mint_a, &mint_b], blockhash);
+    rpc_client.send_and_confirm_transaction(&mint_tx)?;
+
+    let blockhash = rpc_client.get_latest_blockhash()?;
+    let mut init_tx = Transaction::new_signed_with_payer(
+        &[
+            // INITIALIZE (fee of 100%)
+            instruction::initialize_exchange_booth(
+                &exchange_booth_program_id,
+                &admin.pubkey(),
+                &mint_a.pubkey(),
+                &mint_b.pubkey(),
+                &oracle,
+                pricing::BPS,
+                100,
+                &echo_program_id,
+                &admin.pubkey(),
+            ),
+        ],
+        Some(&admin.pubkey()),
+        &vec![&admin],
+        blockhash,
+    );
+    init_tx.sign(&vec![&admin], blockhash);
+    assert_exchange_booth_error(&rpc_client, &init_tx, ExchangeBoothError::InvalidInstructionInput);
+
+    let mut init_ix = instruction::initialize_exchange_booth(
+        &exchange_booth_program_id,
+        &admin.pubkey(),
+        &mint_a.pubkey(),
+        &mint_b.pubkey(),
+        &oracle,
+        100,
+        100,
+        &echo_program_id,
+        &admin.pubkey(),
+    );
+    init_ix.accounts[3].pubkey = Pubkey::new_unique();
+    let blockhash = rpc_client.get_latest_blockhash()?;
+    let mut init_tx = Transaction::new_signed_with_payer(
+        &[
+            // INITIALIZE (vault_a is not the vault's program address)
+            init_ix,
+        ],
+        Some(&admin.pubkey()),
+        &vec![&admin],
+        blockhash,
+    );
+    init_tx.sign(&vec![&admin], blockhash);
+    assert_exchange_booth_error(&rpc_client, &init_tx, ExchangeBoothError::InvalidProgramAddress);
 
-    init_tx.sign(&vec![&admin, &mint_a, &mint_b], blockhash);
+    let blockhash = rpc_client.get_latest_blockhash()?;
+    let mut init_tx = Transaction::new_signed_with_payer(
+        &[
+            // INITIALIZE
+            instruction::initialize_exchange_booth(
+                &exchange_booth_program_id,
+                &admin.pubkey(),
+                &mint_a.pubkey(),
+                &mint_b.pubkey(),
+                &oracle,
+                100,
+                100,
+                &echo_program_id,
+                &admin.pubkey(),
+            ),
+        ],
+        Some(&admin.pubkey()),
+        &vec![&admin],
+        blockhash,
+    );
+    init_tx.sign(&vec![&admin], blockhash);
     rpc_client.send_and_confirm_transaction(&init_tx)?;
 
     let blockhash = rpc_client.get_latest_blockhash().unwrap();
@@ -248,6 +359,8 @@ fn test_exchange_booth() -> anyhow::Result<()> {
                 &[],
                 42,
             )?,
+            // the user pays for the transactions the admin must not sign
+            system_instruction::transfer(&admin.pubkey(), &user.pubkey(), 1_000_000_000),
         ],
         Some(&admin.pubkey()),
         &vec![&user_wallet_a, &user_wallet_b, &admin],
@@ -260,17 +373,14 @@ fn test_exchange_booth() -> anyhow::Result<()> {
     let mut deposit_tx = Transaction::new_signed_with_payer(
         &[
             // DEPOSIT
-            Instruction {
-                program_id: exchange_booth_program_id,
-                accounts: vec![
-                    AccountMeta::new(admin_wallet_a.pubkey(), false),
-                    AccountMeta::new(vault_a, false),
-                    AccountMeta::new_readonly(admin.pubkey(), true),
-                    AccountMeta::new_readonly(exchange_booth, false),
-                    AccountMeta::new_readonly(spl_token::id(), false),
-                ],
-                data: ExchangeBoothInstruction::Deposit { amount: 42 }.try_to_vec()?,
-            },
+            instruction::deposit(
+                &exchange_booth_program_id,
+                &exchange_booth,
+                &mint_a.pubkey(),
+                &admin_wallet_a.pubkey(),
+                &admin.pubkey(),
+                42,
+            ),
         ],
         Some(&admin.pubkey()),
         &vec![&admin],
@@ -282,9 +392,14 @@ fn test_exchange_booth() -> anyhow::Result<()> {
 
     println!("--- Initialize Exchange Booth ---");
     println!("admin: {:?}\nmint_a: {:?}\nmint_b: {:?}\noracle: {:?}\nexchange_booth: {:?}", admin.pubkey(), mint_a.pubkey(), mint_b.pubkey(), oracle, exchange_booth);
-    let exchange_booth_data = ExchangeBooth::try_from_slice(&rpc_client.get_account(&exchange_booth)?.data)?;
+    let exchange_booth_data: ExchangeBooth = try_from_slice_unchecked(&rpc_client.get_account(&exchange_booth)?.data)?;
     println!("{:?}", exchange_booth_data);
     println!();
+    assert_eq!(rpc_client.get_account(&exchange_booth)?.data.len(), ExchangeBooth::LEN);
+    assert_eq!(exchange_booth_data.version, ExchangeBooth::VERSION);
+    assert_eq!(exchange_booth_data.bump, exchange_booth_bump);
+    assert_eq!(exchange_booth_data.vault_a_bump, vault_a_bump);
+    assert_eq!(exchange_booth_data.vault_b_bump, vault_b_bump);
     let vault_a_account = spl_token::state::Account::unpack(&rpc_client.get_account(&vault_a)?.data)?;
     let vault_b_account = spl_token::state::Account::unpack(&rpc_client.get_account(&vault_b)?.data)?;
     let wallet_a_account = spl_token::state::Account::unpack(&rpc_client.get_account(&admin_wallet_a.pubkey())?.data)?;
@@ -303,17 +418,14 @@ fn test_exchange_booth() -> anyhow::Result<()> {
     let mut deposit_tx = Transaction::new_signed_with_payer(
         &[
             // DEPOSIT
-            Instruction {
-                program_id: exchange_booth_program_id,
-                accounts: vec![
-                    AccountMeta::new(admin_wallet_b.pubkey(), false),
-                    AccountMeta::new(vault_b, false),
-                    AccountMeta::new_readonly(admin.pubkey(), true),
-                    AccountMeta::new_readonly(exchange_booth, false),
-                    AccountMeta::new_readonly(spl_token::id(), false),
-                ],
-                data: ExchangeBoothInstruction::Deposit { amount: 42 }.try_to_vec()?,
-            },
+            instruction::deposit(
+                &exchange_booth_program_id,
+                &exchange_booth,
+                &mint_b.pubkey(),
+                &admin_wallet_b.pubkey(),
+                &admin.pubkey(),
+                42,
+            ),
         ],
         Some(&admin.pubkey()),
         &vec![&admin],
@@ -341,27 +453,44 @@ fn test_exchange_booth() -> anyhow::Result<()> {
     let mut withdraw_tx = Transaction::new_signed_with_payer(
         &[
             // WITHDRAW
-            Instruction {
-                program_id: exchange_booth_program_id,
-                accounts: vec![
-                    AccountMeta::new(vault_a, false),
-                    AccountMeta::new(admin_wallet_a.pubkey(), false),
-                    AccountMeta::new_readonly(mint_a.pubkey(), false),
-                    AccountMeta::new_readonly(admin.pubkey(), true),
-                    AccountMeta::new_readonly(exchange_booth, false),
-                    AccountMeta::new_readonly(spl_token::id(), false),
-                ],
-                data: ExchangeBoothInstruction::Withdraw { amount: 21 }.try_to_vec()?,
-            },
+            instruction::withdraw(
+                &exchange_booth_program_id,
+                &exchange_booth,
+                &mint_a.pubkey(),
+                &admin_wallet_a.pubkey(),
+                &admin.pubkey(),
+                21,
+            ),
         ],
         Some(&admin.pubkey()),
         &vec![&admin],
         blockhash,
     );
     withdraw_tx.sign(&vec![&admin], blockhash);
+    let withdraw_units = compute_units_consumed(&rpc_client, &withdraw_tx, &exchange_booth_program_id)?;
+    let mut withdraw_b_tx = Transaction::new_signed_with_payer(
+        &[
+            // WITHDRAW (simulated, out of vault_b)
+            instruction::withdraw(
+                &exchange_booth_program_id,
+                &exchange_booth,
+                &mint_b.pubkey(),
+                &admin_wallet_b.pubkey(),
+                &admin.pubkey(),
+                21,
+            ),
+        ],
+        Some(&admin.pubkey()),
+        &vec![&admin],
+        blockhash,
+    );
+    withdraw_b_tx.sign(&vec![&admin], blockhash);
+    let withdraw_b_units = compute_units_consumed(&rpc_client, &withdraw_b_tx, &exchange_booth_program_id)?;
     rpc_client.send_and_confirm_transaction(&withdraw_tx)?;
 
     println!("--- Withdraw ---");
+    println!("consumed {:?} compute units, {:?} out of vault_b", withdraw_units, withdraw_b_units);
+    assert!(withdraw_units.abs_diff(withdraw_b_units) < BUMP_SEARCH_TOLERANCE);
     let vault_a_account = spl_token::state::Account::unpack(&rpc_client.get_account(&vault_a)?.data)?;
     let vault_b_account = spl_token::state::Account::unpack(&rpc_client.get_account(&vault_b)?.data)?;
     let wallet_a_account = spl_token::state::Account::unpack(&rpc_client.get_account(&admin_wallet_a.pubkey())?.data)?;
@@ -376,20 +505,122 @@ fn test_exchange_booth() -> anyhow::Result<()> {
     println!("\u{001b}[36m user_wallet_b \u{001b}[0m {:?}", user_wallet_b_account);
     println!();
 
+    let blockhash = rpc_client.get_latest_blockhash()?;
+    let mut withdraw_tx = Transaction::new_signed_with_payer(
+        &[
+            // WITHDRAW (more than the vault holds)
+            instruction::withdraw(
+                &exchange_booth_program_id,
+                &exchange_booth,
+                &mint_a.pubkey(),
+                &admin_wallet_a.pubkey(),
+                &admin.pubkey(),
+                vault_a_account.amount + 1,
+            ),
+        ],
+        Some(&admin.pubkey()),
+        &vec![&admin],
+        blockhash,
+    );
+    withdraw_tx.sign(&vec![&admin], blockhash);
+    assert_exchange_booth_error(&rpc_client, &withdraw_tx, ExchangeBoothError::InsufficientVaultBalance);
+
+    let blockhash = rpc_client.get_latest_blockhash()?;
+    let mut withdraw_tx = Transaction::new_signed_with_payer(
+        &[
+            // WITHDRAW (wrong mint)
+            Instruction {
+                program_id: exchange_booth_program_id,
+                accounts: vec![
+                    AccountMeta::new(vault_a, false),
+                    AccountMeta::new(admin_wallet_b.pubkey(), false),
+                    AccountMeta::new_readonly(mint_b.pubkey(), false),
+                    AccountMeta::new_readonly(admin.pubkey(), true),
+                    AccountMeta::new(exchange_booth, false),
+                    AccountMeta::new_readonly(spl_token::id(), false),
+                ],
+                data: ExchangeBoothInstruction::Withdraw { amount: 1 }.try_to_vec()?,
+            },
+        ],
+        Some(&admin.pubkey()),
+        &vec![&admin],
+        blockhash,
+    );
+    withdraw_tx.sign(&vec![&admin], blockhash);
+    assert_exchange_booth_error(&rpc_client, &withdraw_tx, ExchangeBoothError::MintMismatch);
+
+    let mut withdraw_ix = instruction::withdraw(
+        &exchange_booth_program_id,
+        &exchange_booth,
+        &mint_a.pubkey(),
+        &admin_wallet_a.pubkey(),
+        &admin.pubkey(),
+        1,
+    );
+    withdraw_ix.accounts[3].is_signer = false;
+    let blockhash = rpc_client.get_latest_blockhash()?;
+    let mut withdraw_tx = Transaction::new_signed_with_payer(
+        &[
+            // WITHDRAW (admin did not sign)
+            withdraw_ix,
+        ],
+        Some(&user.pubkey()),
+        &vec![&user],
+        blockhash,
+    );
+    withdraw_tx.sign(&vec![&user], blockhash);
+    assert_exchange_booth_error(&rpc_client, &withdraw_tx, ExchangeBoothError::MissingRequiredSignature);
+
+    let blockhash = rpc_client.get_latest_blockhash()?;
+    let mut deposit_tx = Transaction::new_signed_with_payer(
+        &[
+            // DEPOSIT (source is not a token account)
+            instruction::deposit(
+                &exchange_booth_program_id,
+                &exchange_booth,
+                &mint_a.pubkey(),
+                &user.pubkey(),
+                &admin.pubkey(),
+                1,
+            ),
+        ],
+        Some(&admin.pubkey()),
+        &vec![&admin],
+        blockhash,
+    );
+    deposit_tx.sign(&vec![&admin], blockhash);
+    assert_exchange_booth_error(&rpc_client, &deposit_tx, ExchangeBoothError::InvalidAccountOwner);
+
+    let mut deposit_ix = instruction::deposit(
+        &exchange_booth_program_id,
+        &exchange_booth,
+        &mint_a.pubkey(),
+        &admin_wallet_a.pubkey(),
+        &admin.pubkey(),
+        1,
+    );
+    deposit_ix.accounts[3].is_writable = false;
+    let blockhash = rpc_client.get_latest_blockhash()?;
+    let mut deposit_tx = Transaction::new_signed_with_payer(
+        &[
+            // DEPOSIT (booth not writable)
+            deposit_ix,
+        ],
+        Some(&admin.pubkey()),
+        &vec![&admin],
+        blockhash,
+    );
+    deposit_tx.sign(&vec![&admin], blockhash);
+    assert_exchange_booth_error(&rpc_client, &deposit_tx, ExchangeBoothError::AccountMustBeWritable);
+
     let blockhash = rpc_client.get_latest_blockhash()?;
     let mut transaction = Transaction::new_signed_with_payer(
-        &[Instruction {
-            program_id: echo_program_id,
-            accounts: vec![
-                AccountMeta::new(oracle, false),
-                AccountMeta::new(admin.pubkey(), true),
-                AccountMeta::new_readonly(system_program::id(), false),
-            ],
-            data: EchoInstruction::InitializeAuthorizedEcho {
-                buffer_seed,
-                buffer_size: 17,
-            }.try_to_vec()?,
-        }],
+        &[echo::instruction::initialize_authorized_echo(
+            &echo_program_id,
+            &admin.pubkey(),
+            buffer_seed,
+            Oracle::HEADER_LEN + Oracle::LEN,
+        )],
         Some(&admin.pubkey()),
         &vec![&admin],
         blockhash,
@@ -398,18 +629,10 @@ fn test_exchange_booth() -> anyhow::Result<()> {
     rpc_client.send_and_confirm_transaction(&transaction)?;
     let account = rpc_client.get_account(&oracle)?;
 
+    let oracle_data = Oracle { mantissa: 20, exponent: -1, last_update_slot: rpc_client.get_slot()? };
     let blockhash = rpc_client.get_latest_blockhash()?;
     let mut transaction = Transaction::new_signed_with_payer(
-        &[Instruction {
-            program_id: echo_program_id,
-            accounts: vec![
-                AccountMeta::new(oracle, false),
-                AccountMeta::new(admin.pubkey(), true),
-            ],
-            data: EchoInstruction::AuthorizedEcho {
-                data: Oracle { exchange_rate: 2 }.try_to_vec()?
-            }.try_to_vec()?,
-        }],
+        &[echo::instruction::authorized_echo(&echo_program_id, &admin.pubkey(), buffer_seed, oracle_data.try_to_vec()?)],
         Some(&admin.pubkey()),
         &vec![&admin],
         blockhash,
@@ -418,37 +641,100 @@ fn test_exchange_booth() -> anyhow::Result<()> {
     rpc_client.send_and_confirm_transaction(&transaction)?;
     let account = rpc_client.get_account(&oracle)?;
     println!("--- Oracle ---");
-    println!("{:?}", Oracle { exchange_rate: 2 });
+    println!("{:?}", oracle_data);
+    println!();
+
+    let blockhash = rpc_client.get_latest_blockhash().unwrap();
+    let mut slippage_tx = Transaction::new_signed_with_payer(
+        &[
+            // EXCHANGE (below min_amount_out)
+            instruction::exchange(
+                &exchange_booth_program_id,
+                &exchange_booth,
+                &oracle,
+                &mint_a.pubkey(),
+                &mint_b.pubkey(),
+                &user_wallet_a.pubkey(),
+                &user_wallet_b.pubkey(),
+                &user.pubkey(),
+                21,
+                42,
+            ),
+        ],
+        Some(&admin.pubkey()),
+        &vec![&admin, &user],
+        blockhash,
+    );
+    slippage_tx.sign(&vec![&admin, &user], blockhash);
+    assert_exchange_booth_error(&rpc_client, &slippage_tx, ExchangeBoothError::SlippageExceeded);
+
+    let blockhash = rpc_client.get_latest_blockhash()?;
+    let mut quote_tx = Transaction::new_signed_with_payer(
+        &[
+            // QUOTE
+            instruction::quote(
+                &exchange_booth_program_id,
+                &exchange_booth,
+                &oracle,
+                &mint_a.pubkey(),
+                &mint_b.pubkey(),
+                21,
+                Direction::AToB,
+            ),
+        ],
+        Some(&admin.pubkey()),
+        &vec![&admin],
+        blockhash,
+    );
+    quote_tx.sign(&vec![&admin], blockhash);
+    let quote = QuoteResult::from_logs(&exchange_booth_program_id, &simulate_logs(&rpc_client, &quote_tx)?)
+        .ok_or_else(|| anyhow!("no quote returned"))?;
+
+    println!("--- Quote ---");
+    println!("{:?}", quote);
     println!();
+    // the exchange below pays out exactly what was quoted
+    assert_eq!(quote.amount_out, 41);
+    assert_eq!(quote.fee, 1);
+    assert_eq!((quote.mantissa, quote.exponent), (oracle_data.mantissa, oracle_data.exponent));
+    assert_eq!(quote.last_update_slot, oracle_data.last_update_slot);
 
     let blockhash = rpc_client.get_latest_blockhash().unwrap();
     let mut exchange_tx = Transaction::new_signed_with_payer(
         &[
             // EXCHANGE
-            Instruction {
-                program_id: exchange_booth_program_id,
-                accounts: vec![
-                    AccountMeta::new(user_wallet_a.pubkey(), false),
-                    AccountMeta::new(vault_a, false),
-                    AccountMeta::new(vault_b, false),
-                    AccountMeta::new(user_wallet_b.pubkey(), false),
-                    AccountMeta::new_readonly(mint_b.pubkey(), false),
-                    AccountMeta::new_readonly(user.pubkey(), true),
-                    AccountMeta::new_readonly(oracle, false),
-                    AccountMeta::new_readonly(exchange_booth, false),
-                    AccountMeta::new_readonly(spl_token::id(), false),
-                ],
-                data: ExchangeBoothInstruction::Exchange { amount: 21 }.try_to_vec()?,
-            },
+            instruction::exchange(
+                &exchange_booth_program_id,
+                &exchange_booth,
+                &oracle,
+                &mint_a.pubkey(),
+                &mint_b.pubkey(),
+                &user_wallet_a.pubkey(),
+                &user_wallet_b.pubkey(),
+                &user.pubkey(),
+                21,
+                41,
+            ),
         ],
         Some(&admin.pubkey()),
         &vec![&admin, &user],
         blockhash,
     );
     exchange_tx.sign(&vec![&admin, &user], blockhash);
+    let exchange_units = compute_units_consumed(&rpc_client, &exchange_tx, &exchange_booth_program_id)?;
+    let exchange_events = decode_events(&exchange_booth_program_id, &simulate_logs(&rpc_client, &exchange_tx)?);
     rpc_client.send_and_confirm_transaction(&exchange_tx)?;
 
     println!("--- Exchange ---");
+    println!("consumed {:?} compute units", exchange_units);
+    assert_eq!(exchange_events, vec![ExchangeBoothEvent::Exchanged {
+        exchange_booth,
+        authority: user.pubkey(),
+        a_to_b: true,
+        amount_in: 21,
+        amount_out: 41,
+        fee: 1,
+    }]);
     let vault_a_account = spl_token::state::Account::unpack(&rpc_client.get_account(&vault_a)?.data)?;
     let vault_b_account = spl_token::state::Account::unpack(&rpc_client.get_account(&vault_b)?.data)?;
     let wallet_a_account = spl_token::state::Account::unpack(&rpc_client.get_account(&admin_wallet_a.pubkey())?.data)?;
@@ -463,24 +749,695 @@ fn test_exchange_booth() -> anyhow::Result<()> {
     println!("\u{001b}[36m user_wallet_b \u{001b}[0m {:?}", user_wallet_b_account);
 
     let blockhash = rpc_client.get_latest_blockhash().unwrap();
-    let mut close_tx = Transaction::new_signed_with_payer(
+    let mut exchange_tx = Transaction::new_signed_with_payer(
         &[
-            // CLOSE
-            Instruction {
-                program_id: exchange_booth_program_id,
-                accounts: vec![
-                    AccountMeta::new(admin.pubkey(), true),
-                    AccountMeta::new(exchange_booth, false),
-                ],
-                data: ExchangeBoothInstruction::CloseExchangeBooth.try_to_vec()?,
-            },
-        ],
-        Some(&admin.pubkey()),
+            // EXCHANGE B -> A
+            instruction::exchange(
+                &exchange_booth_program_id,
+                &exchange_booth,
+                &oracle,
+                &mint_b.pubkey(),
+                &mint_a.pubkey(),
+                &user_wallet_b.pubkey(),
+                &user_wallet_a.pubkey(),
+                &user.pubkey(),
+                20,
+                9,
+            ),
+        ],
+        Some(&admin.pubkey()),
+        &vec![&admin, &user],
+        blockhash,
+    );
+    exchange_tx.sign(&vec![&admin, &user], blockhash);
+    let exchange_b_to_a_units = compute_units_consumed(&rpc_client, &exchange_tx, &exchange_booth_program_id)?;
+    rpc_client.send_and_confirm_transaction(&exchange_tx)?;
+
+    println!("--- Exchange B -> A ---");
+    println!("consumed {:?} compute units", exchange_b_to_a_units);
+    // A -> B pays out of vault_b and B -> A out of vault_a
+    assert!(exchange_units.abs_diff(exchange_b_to_a_units) < BUMP_SEARCH_TOLERANCE);
+    let vault_a_account = spl_token::state::Account::unpack(&rpc_client.get_account(&vault_a)?.data)?;
+    let vault_b_account = spl_token::state::Account::unpack(&rpc_client.get_account(&vault_b)?.data)?;
+    let user_wallet_a_account = spl_token::state::Account::unpack(&rpc_client.get_account(&user_wallet_a.pubkey())?.data)?;
+    let user_wallet_b_account = spl_token::state::Account::unpack(&rpc_client.get_account(&user_wallet_b.pubkey())?.data)?;
+    println!("\u{001b}[36m vault_a \u{001b}[0m {:?}", vault_a_account);
+    println!("\u{001b}[36m vault_b \u{001b}[0m {:?}", vault_b_account);
+    println!("\u{001b}[36m user_wallet_a \u{001b}[0m {:?}", user_wallet_a_account);
+    println!("\u{001b}[36m user_wallet_b \u{001b}[0m {:?}", user_wallet_b_account);
+    // 1% fee, rounded up: 21 A -> 42 - 1 B, then 20 B -> 10 - 1 A
+    assert_eq!(user_wallet_a_account.amount, 42 - 21 + 9);
+    assert_eq!(user_wallet_b_account.amount, 42 + 41 - 20);
+    println!();
+
+    let blockhash = rpc_client.get_latest_blockhash()?;
+    let mut slippage_tx = Transaction::new_signed_with_payer(
+        &[
+            // EXCHANGE EXACT OUT (above max_amount_in)
+            instruction::exchange_exact_out(
+                &exchange_booth_program_id,
+                &exchange_booth,
+                &oracle,
+                &mint_a.pubkey(),
+                &mint_b.pubkey(),
+                &user_wallet_a.pubkey(),
+                &user_wallet_b.pubkey(),
+                &user.pubkey(),
+                10,
+                5,
+            ),
+        ],
+        Some(&admin.pubkey()),
+        &vec![&admin, &user],
+        blockhash,
+    );
+    slippage_tx.sign(&vec![&admin, &user], blockhash);
+    assert_exchange_booth_error(&rpc_client, &slippage_tx, ExchangeBoothError::SlippageExceeded);
+
+    let blockhash = rpc_client.get_latest_blockhash().unwrap();
+    let mut exchange_tx = Transaction::new_signed_with_payer(
+        &[
+            // EXCHANGE EXACT OUT
+            instruction::exchange_exact_out(
+                &exchange_booth_program_id,
+                &exchange_booth,
+                &oracle,
+                &mint_a.pubkey(),
+                &mint_b.pubkey(),
+                &user_wallet_a.pubkey(),
+                &user_wallet_b.pubkey(),
+                &user.pubkey(),
+                10,
+                6,
+            ),
+        ],
+        Some(&admin.pubkey()),
+        &vec![&admin, &user],
+        blockhash,
+    );
+    exchange_tx.sign(&vec![&admin, &user], blockhash);
+    rpc_client.send_and_confirm_transaction(&exchange_tx)?;
+
+    println!("--- Exchange Exact Out ---");
+    let user_wallet_a_account = spl_token::state::Account::unpack(&rpc_client.get_account(&user_wallet_a.pubkey())?.data)?;
+    let user_wallet_b_account = spl_token::state::Account::unpack(&rpc_client.get_account(&user_wallet_b.pubkey())?.data)?;
+    println!("\u{001b}[36m user_wallet_a \u{001b}[0m {:?}", user_wallet_a_account);
+    println!("\u{001b}[36m user_wallet_b \u{001b}[0m {:?}", user_wallet_b_account);
+    // 10 B after a 1% fee needs 11 B before fees, which costs ceil(11 / 2) = 6 A
+    assert_eq!(user_wallet_a_account.amount, 42 - 21 + 9 - 6);
+    assert_eq!(user_wallet_b_account.amount, 42 + 41 - 20 + 10);
+    println!();
+
+    let new_buffer_seed = 43u64;
+    let (new_oracle, _) = find_authorized_buffer_address(&echo_program_id, &admin.pubkey(), new_buffer_seed);
+    let blockhash = rpc_client.get_latest_blockhash()?;
+    let mut transaction = Transaction::new_signed_with_payer(
+        &[
+            echo::instruction::initialize_authorized_echo(
+                &echo_program_id,
+                &admin.pubkey(),
+                new_buffer_seed,
+                Oracle::HEADER_LEN + Oracle::LEN,
+            ),
+            echo::instruction::authorized_echo(&echo_program_id, &admin.pubkey(), new_buffer_seed, oracle_data.try_to_vec()?),
+            // UPDATE ORACLE
+            instruction::update_oracle(
+                &exchange_booth_program_id,
+                &exchange_booth,
+                &admin.pubkey(),
+                &new_oracle,
+                &admin.pubkey(),
+            ),
+        ],
+        Some(&admin.pubkey()),
+        &vec![&admin],
+        blockhash,
+    );
+    transaction.sign(&[&admin], blockhash);
+    rpc_client.send_and_confirm_transaction(&transaction)?;
+
+    println!("--- Update Oracle ---");
+    let exchange_booth_data: ExchangeBooth = try_from_slice_unchecked(&rpc_client.get_account(&exchange_booth)?.data)?;
+    println!("{:?}", exchange_booth_data);
+    assert_eq!(exchange_booth_data.oracle, new_oracle);
+    println!();
+
+    let new_admin = Keypair::new();
+    let blockhash = rpc_client.get_latest_blockhash()?;
+    let mut transaction = Transaction::new_signed_with_payer(
+        &[
+            // PROPOSE ADMIN
+            instruction::propose_admin(
+                &exchange_booth_program_id,
+                &exchange_booth,
+                &admin.pubkey(),
+                &new_admin.pubkey(),
+            ),
+            // ACCEPT ADMIN
+            instruction::accept_admin(
+                &exchange_booth_program_id,
+                &exchange_booth,
+                &new_admin.pubkey(),
+            ),
+        ],
+        Some(&admin.pubkey()),
+        &vec![&admin, &new_admin],
+        blockhash,
+    );
+    transaction.sign(&[&admin, &new_admin], blockhash);
+    rpc_client.send_and_confirm_transaction(&transaction)?;
+
+    println!("--- Transfer Admin ---");
+    let exchange_booth_data: ExchangeBooth = try_from_slice_unchecked(&rpc_client.get_account(&exchange_booth)?.data)?;
+    println!("{:?}", exchange_booth_data);
+    assert_eq!(exchange_booth_data.admin, new_admin.pubkey());
+    assert_eq!(exchange_booth_data.pending_admin, Pubkey::default());
+    println!();
+
+    // hand the booth back so the original admin can close it
+    let blockhash = rpc_client.get_latest_blockhash()?;
+    let mut transaction = Transaction::new_signed_with_payer(
+        &[
+            instruction::propose_admin(
+                &exchange_booth_program_id,
+                &exchange_booth,
+                &new_admin.pubkey(),
+                &admin.pubkey(),
+            ),
+            instruction::accept_admin(
+                &exchange_booth_program_id,
+                &exchange_booth,
+                &admin.pubkey(),
+            ),
+        ],
+        Some(&admin.pubkey()),
+        &vec![&admin, &new_admin],
+        blockhash,
+    );
+    transaction.sign(&[&admin, &new_admin], blockhash);
+    rpc_client.send_and_confirm_transaction(&transaction)?;
+
+    let blockhash = rpc_client.get_latest_blockhash()?;
+    let mut pause_tx = Transaction::new_signed_with_payer(
+        &[
+            // PAUSE
+            instruction::set_paused(
+                &exchange_booth_program_id,
+                &exchange_booth,
+                &admin.pubkey(),
+                true,
+            ),
+        ],
+        Some(&admin.pubkey()),
+        &vec![&admin],
+        blockhash,
+    );
+    pause_tx.sign(&vec![&admin], blockhash);
+    rpc_client.send_and_confirm_transaction(&pause_tx)?;
+
+    let blockhash = rpc_client.get_latest_blockhash()?;
+    let mut deposit_tx = Transaction::new_signed_with_payer(
+        &[
+            // DEPOSIT (paused)
+            instruction::deposit(
+                &exchange_booth_program_id,
+                &exchange_booth,
+                &mint_a.pubkey(),
+                &admin_wallet_a.pubkey(),
+                &admin.pubkey(),
+                1,
+            ),
+        ],
+        Some(&admin.pubkey()),
+        &vec![&admin],
+        blockhash,
+    );
+    deposit_tx.sign(&vec![&admin], blockhash);
+    assert_exchange_booth_error(&rpc_client, &deposit_tx, ExchangeBoothError::ExchangeBoothPaused);
+
+    let blockhash = rpc_client.get_latest_blockhash()?;
+    let mut withdraw_tx = Transaction::new_signed_with_payer(
+        &[
+            // WITHDRAW (paused)
+            instruction::withdraw(
+                &exchange_booth_program_id,
+                &exchange_booth,
+                &mint_a.pubkey(),
+                &admin_wallet_a.pubkey(),
+                &admin.pubkey(),
+                1,
+            ),
+            // UNPAUSE
+            instruction::set_paused(
+                &exchange_booth_program_id,
+                &exchange_booth,
+                &admin.pubkey(),
+                false,
+            ),
+        ],
+        Some(&admin.pubkey()),
+        &vec![&admin],
+        blockhash,
+    );
+    withdraw_tx.sign(&vec![&admin], blockhash);
+    rpc_client.send_and_confirm_transaction(&withdraw_tx)?;
+
+    let blockhash = rpc_client.get_latest_blockhash()?;
+    let mut deposit_tx = Transaction::new_signed_with_payer(
+        &[
+            // DEPOSIT (not a vault)
+            Instruction {
+                program_id: exchange_booth_program_id,
+                accounts: vec![
+                    AccountMeta::new(admin_wallet_a.pubkey(), false),
+                    AccountMeta::new(user_wallet_a.pubkey(), false),
+                    AccountMeta::new_readonly(admin.pubkey(), true),
+                    AccountMeta::new(exchange_booth, false),
+                    AccountMeta::new_readonly(spl_token::id(), false),
+                ],
+                data: ExchangeBoothInstruction::Deposit { amount: 1 }.try_to_vec()?,
+            },
+        ],
+        Some(&admin.pubkey()),
+        &vec![&admin],
+        blockhash,
+    );
+    deposit_tx.sign(&vec![&admin], blockhash);
+    assert_exchange_booth_error(&rpc_client, &deposit_tx, ExchangeBoothError::InvalidAccountAddress);
+
+    let blockhash = rpc_client.get_latest_blockhash()?;
+    let mut deposit_tx = Transaction::new_signed_with_payer(
+        &[
+            // DEPOSIT (wrong mint)
+            instruction::deposit(
+                &exchange_booth_program_id,
+                &exchange_booth,
+                &mint_a.pubkey(),
+                &admin_wallet_b.pubkey(),
+                &admin.pubkey(),
+                1,
+            ),
+        ],
+        Some(&admin.pubkey()),
+        &vec![&admin],
+        blockhash,
+    );
+    deposit_tx.sign(&vec![&admin], blockhash);
+    assert_exchange_booth_error(&rpc_client, &deposit_tx, ExchangeBoothError::MintMismatch);
+
+    let user_deposit_ix = instruction::deposit(
+        &exchange_booth_program_id,
+        &exchange_booth,
+        &mint_a.pubkey(),
+        &user_wallet_a.pubkey(),
+        &user.pubkey(),
+        1,
+    );
+    let blockhash = rpc_client.get_latest_blockhash()?;
+    let mut deposit_tx = Transaction::new_signed_with_payer(
+        &[
+            // DEPOSIT (user, public liquidity disabled)
+            user_deposit_ix.clone(),
+        ],
+        Some(&admin.pubkey()),
+        &vec![&admin, &user],
+        blockhash,
+    );
+    deposit_tx.sign(&vec![&admin, &user], blockhash);
+    assert_exchange_booth_error(&rpc_client, &deposit_tx, ExchangeBoothError::IncorrectAdmin);
+
+    let blockhash = rpc_client.get_latest_blockhash()?;
+    let mut deposit_tx = Transaction::new_signed_with_payer(
+        &[
+            // ENABLE PUBLIC LIQUIDITY
+            instruction::set_public_liquidity(
+                &exchange_booth_program_id,
+                &exchange_booth,
+                &admin.pubkey(),
+                true,
+            ),
+            // DEPOSIT (user)
+            user_deposit_ix,
+        ],
+        Some(&admin.pubkey()),
+        &vec![&admin, &user],
+        blockhash,
+    );
+    deposit_tx.sign(&vec![&admin, &user], blockhash);
+    rpc_client.send_and_confirm_transaction(&deposit_tx)?;
+
+    println!("--- Public Liquidity ---");
+    let user_wallet_a_account = spl_token::state::Account::unpack(&rpc_client.get_account(&user_wallet_a.pubkey())?.data)?;
+    let user_wallet_b_account = spl_token::state::Account::unpack(&rpc_client.get_account(&user_wallet_b.pubkey())?.data)?;
+    println!("\u{001b}[36m user_wallet_a \u{001b}[0m {:?}", user_wallet_a_account);
+    println!();
+    let exchange_booth_data: ExchangeBooth = try_from_slice_unchecked(&rpc_client.get_account(&exchange_booth)?.data)?;
+    assert!(exchange_booth_data.is_public_liquidity());
+
+    // 42 + 1 in and 21 + 1 out of vault_a, 42 into vault_b; A -> B trades of 21 and 6 paid fees
+    // of 1 each in mint_b and the B -> A trade of 20 paid 1 in mint_a
+    assert_eq!(exchange_booth_data.total_deposited_a, 43);
+    assert_eq!(exchange_booth_data.total_deposited_b, 42);
+    assert_eq!(exchange_booth_data.total_withdrawn_a, 22);
+    assert_eq!(exchange_booth_data.total_withdrawn_b, 0);
+    assert_eq!(exchange_booth_data.volume_a_to_b, 27);
+    assert_eq!(exchange_booth_data.volume_b_to_a, 20);
+    assert_eq!(exchange_booth_data.fees_collected_a, 1);
+    assert_eq!(exchange_booth_data.fees_collected_b, 2);
+    assert_eq!(exchange_booth_data.trade_count, 3);
+
+    let blockhash = rpc_client.get_latest_blockhash()?;
+    let mut close_tx = Transaction::new_signed_with_payer(
+        &[
+            // CLOSE (not the admin)
+            instruction::close_exchange_booth(
+                &exchange_booth_program_id,
+                &exchange_booth,
+                &user.pubkey(),
+                &mint_a.pubkey(),
+                &mint_b.pubkey(),
+                &user_wallet_a.pubkey(),
+                &user_wallet_b.pubkey(),
+            ),
+        ],
+        Some(&admin.pubkey()),
+        &vec![&admin, &user],
+        blockhash,
+    );
+    close_tx.sign(&vec![&admin, &user], blockhash);
+    assert_exchange_booth_error(&rpc_client, &close_tx, ExchangeBoothError::IncorrectAdmin);
+
+    let mut close_ix = instruction::close_exchange_booth(
+        &exchange_booth_program_id,
+        &exchange_booth,
+        &admin.pubkey(),
+        &mint_a.pubkey(),
+        &mint_b.pubkey(),
+        &admin_wallet_a.pubkey(),
+        &admin_wallet_b.pubkey(),
+    );
+    // a token account of the right mint that is not the booth's vault
+    close_ix.accounts[2].pubkey = user_wallet_a.pubkey();
+    let blockhash = rpc_client.get_latest_blockhash()?;
+    let mut close_tx = Transaction::new_signed_with_payer(
+        &[
+            // CLOSE (not a vault)
+            close_ix,
+        ],
+        Some(&admin.pubkey()),
+        &vec![&admin],
+        blockhash,
+    );
+    close_tx.sign(&vec![&admin], blockhash);
+    assert_exchange_booth_error(&rpc_client, &close_tx, ExchangeBoothError::InvalidAccountAddress);
+
+    let blockhash = rpc_client.get_latest_blockhash().unwrap();
+    let mut close_tx = Transaction::new_signed_with_payer(
+        &[
+            // CLOSE
+            instruction::close_exchange_booth(
+                &exchange_booth_program_id,
+                &exchange_booth,
+                &admin.pubkey(),
+                &mint_a.pubkey(),
+                &mint_b.pubkey(),
+                &admin_wallet_a.pubkey(),
+                &admin_wallet_b.pubkey(),
+            ),
+        ],
+        Some(&admin.pubkey()),
         &vec![&admin],
         blockhash,
     );
     close_tx.sign(&vec![&admin], blockhash);
     rpc_client.send_and_confirm_transaction(&close_tx)?;
+
+    println!("--- Close ---");
+    let wallet_a_account = spl_token::state::Account::unpack(&rpc_client.get_account(&admin_wallet_a.pubkey())?.data)?;
+    let wallet_b_account = spl_token::state::Account::unpack(&rpc_client.get_account(&admin_wallet_b.pubkey())?.data)?;
+    println!("\u{001b}[36m admin_wallet_a \u{001b}[0m {:?}", wallet_a_account);
+    println!("\u{001b}[36m admin_wallet_b \u{001b}[0m {:?}", wallet_b_account);
+    // everything the booth held goes back to the admin
+    assert_eq!(wallet_a_account.amount + user_wallet_a_account.amount, 42 + 42);
+    assert_eq!(wallet_b_account.amount + user_wallet_b_account.amount, 42 + 42);
+    assert!(rpc_client.get_account(&vault_a).is_err());
+    assert!(rpc_client.get_account(&vault_b).is_err());
+    assert!(rpc_client.get_account(&exchange_booth).is_err());
     Ok(())
 }
 
+#[test]
+fn test_oracle_checks() -> anyhow::Result<()> {
+    solana_logger::setup_with_default("solana_program_runtime=debug");
+    let exchange_booth_program_id = Pubkey::new_unique();
+    // the echo program is never invoked, it only has to own the oracles
+    let echo_program_id = Pubkey::new_unique();
+    let oracle_authority = Pubkey::new_unique();
+    let user = Keypair::new();
+    let mint_a = Pubkey::new_unique();
+    let mint_b = Pubkey::new_unique();
+    let user_wallet_a = Pubkey::new_unique();
+    let user_wallet_b = Pubkey::new_unique();
+
+    // an authorized echo buffer as the echo program writes it: bump seed, buffer seed, then the rate
+    let oracle_buffer = |buffer_seed: u64, oracle_data: Oracle| -> anyhow::Result<(Pubkey, Vec<u8>)> {
+        let (oracle, bump_seed) = find_authorized_buffer_address(&echo_program_id, &oracle_authority, buffer_seed);
+        let mut data = vec![bump_seed];
+        data.extend_from_slice(&buffer_seed.to_le_bytes());
+        data.extend_from_slice(&oracle_data.try_to_vec()?);
+        Ok((oracle, data))
+    };
+
+    // every oracle below was last updated at slot 0
+    let (fresh_oracle, fresh_data) = oracle_buffer(1, Oracle { mantissa: 20, exponent: -1, last_update_slot: 0 })?;
+    let (stale_oracle, stale_data) = oracle_buffer(2, Oracle { mantissa: 20, exponent: -1, last_update_slot: 0 })?;
+    let (future_oracle, future_data) = oracle_buffer(3, Oracle { mantissa: 20, exponent: -1, last_update_slot: u64::MAX })?;
+    let (unowned_oracle, unowned_data) = oracle_buffer(4, Oracle { mantissa: 20, exponent: -1, last_update_slot: 0 })?;
+    // a buffer whose header names another buffer seed than the one it was derived from
+    let (mismatched_oracle, _) = oracle_buffer(5, Oracle { mantissa: 20, exponent: -1, last_update_slot: 0 })?;
+    let (_, mismatched_data) = oracle_buffer(6, Oracle { mantissa: 20, exponent: -1, last_update_slot: 0 })?;
+    let (no_header_oracle, no_header_data) = oracle_buffer(7, Oracle { mantissa: 20, exponent: -1, last_update_slot: 0 })?;
+    let (truncated_oracle, truncated_data) = oracle_buffer(8, Oracle { mantissa: 20, exponent: -1, last_update_slot: 0 })?;
+
+    // (case, oracle, oracle account, max_staleness_slots, expected error), each traded on its own booth
+    let cases = vec![
+        ("fresh", fresh_oracle, rent_exempt_account(fresh_data, &echo_program_id), u64::MAX, None),
+        ("stale", stale_oracle, rent_exempt_account(stale_data, &echo_program_id), 0, Some(ExchangeBoothError::StaleOracle)),
+        ("updated in the future", future_oracle, rent_exempt_account(future_data, &echo_program_id), 100, Some(ExchangeBoothError::StaleOracle)),
+        ("not owned by the oracle program", unowned_oracle, rent_exempt_account(unowned_data, &Pubkey::new_unique()), u64::MAX, Some(ExchangeBoothError::InvalidAccountOwner)),
+        ("header of another buffer", mismatched_oracle, rent_exempt_account(mismatched_data, &echo_program_id), u64::MAX, Some(ExchangeBoothError::InvalidOracleHeader)),
+        ("shorter than the header", no_header_oracle, rent_exempt_account(no_header_data[..5].to_vec(), &echo_program_id), u64::MAX, Some(ExchangeBoothError::OracleDataTooShort)),
+        ("shorter than the rate", truncated_oracle, rent_exempt_account(truncated_data[..Oracle::HEADER_LEN + 10].to_vec(), &echo_program_id), u64::MAX, Some(ExchangeBoothError::OracleDataTooShort)),
+    ];
+
+    let mut genesis = TestValidatorGenesis::default();
+    genesis
+        .add_program("exchange_booth", exchange_booth_program_id)
+        .add_account(user.pubkey(), AccountSharedData::new(1_000_000_000, 0, &system_program::id()))
+        .add_account(mint_a, mint_account()?)
+        .add_account(mint_b, mint_account()?)
+        .add_account(user_wallet_a, token_account(mint_a, user.pubkey(), 100)?)
+        .add_account(user_wallet_b, token_account(mint_b, user.pubkey(), 0)?);
+    let mut exchange_booths = vec![];
+    for (case, oracle, oracle_account, max_staleness_slots, error) in cases {
+        let admin = Pubkey::new_unique();
+        let (exchange_booth, bump) = find_exchange_booth_address(&exchange_booth_program_id, &admin, &mint_a, &mint_b);
+        let (vault_a, vault_a_bump) = find_vault_address(&exchange_booth_program_id, &exchange_booth, &mint_a);
+        let (vault_b, vault_b_bump) = find_vault_address(&exchange_booth_program_id, &exchange_booth, &mint_b);
+        let exchange_booth_data = ExchangeBooth::new(
+            bump,
+            vault_a_bump,
+            vault_b_bump,
+            0,
+            admin,
+            vault_a,
+            vault_b,
+            oracle,
+            echo_program_id,
+            oracle_authority,
+            max_staleness_slots,
+        );
+        genesis
+            .add_account(exchange_booth, rent_exempt_account(exchange_booth_data.try_to_vec()?, &exchange_booth_program_id))
+            .add_account(vault_a, token_account(mint_a, vault_a, 0)?)
+            .add_account(vault_b, token_account(mint_b, vault_b, 100)?)
+            .add_account(oracle, oracle_account);
+        exchange_booths.push((case, exchange_booth, oracle, error));
+    }
+    let (test_validator, _payer) = genesis.start();
+    let rpc_client = test_validator.get_rpc_client();
+
+    while rpc_client.get_slot()? == 0 {
+        std::thread::sleep(std::time::Duration::from_millis(100));
+    }
+
+    for (case, exchange_booth, oracle, error) in exchange_booths {
+        let blockhash = rpc_client.get_latest_blockhash()?;
+        let mut exchange_tx = Transaction::new_signed_with_payer(
+            &[
+                // EXCHANGE
+                instruction::exchange(
+                    &exchange_booth_program_id,
+                    &exchange_booth,
+                    &oracle,
+                    &mint_a,
+                    &mint_b,
+                    &user_wallet_a,
+                    &user_wallet_b,
+                    &user.pubkey(),
+                    1,
+                    2,
+                ),
+            ],
+            Some(&user.pubkey()),
+            &vec![&user],
+            blockhash,
+        );
+        exchange_tx.sign(&vec![&user], blockhash);
+
+        println!("--- Exchange ({}) ---", case);
+        match error {
+            Some(error) => assert_exchange_booth_error(&rpc_client, &exchange_tx, error),
+            None => {
+                rpc_client.send_and_confirm_transaction(&exchange_tx)?;
+            }
+        }
+    }
+    Ok(())
+}
+
+#[test]
+fn test_migrate_exchange_booth() -> anyhow::Result<()> {
+    solana_logger::setup_with_default("solana_program_runtime=debug");
+    let exchange_booth_program_id = Pubkey::new_unique();
+    let admin = Keypair::new();
+    let mint_a = Pubkey::new_unique();
+    let mint_b = Pubkey::new_unique();
+    let oracle = Pubkey::new_unique();
+
+    // booths created before the version byte were derived with the oracle in their seeds
+    let (exchange_booth, exchange_booth_bump) = find_legacy_exchange_booth_address(
+        &exchange_booth_program_id,
+        &admin.pubkey(),
+        &mint_a,
+        &mint_b,
+        &oracle,
+    );
+    let (vault_a, vault_a_bump) = find_vault_address(&exchange_booth_program_id, &exchange_booth, &mint_a);
+    let (vault_b, vault_b_bump) = find_vault_address(&exchange_booth_program_id, &exchange_booth, &mint_b);
+
+    let legacy_data = ExchangeBoothV1 {
+        is_initialized: true,
+        admin: admin.pubkey(),
+        vault_a,
+        vault_b,
+        oracle,
+    }.try_to_vec()?;
+
+    let (test_validator, _payer) = TestValidatorGenesis::default()
+        .add_program("exchange_booth", exchange_booth_program_id)
+        .add_account(admin.pubkey(), AccountSharedData::new(1_000_000_000, 0, &system_program::id()))
+        .add_account(mint_a, mint_account()?)
+        .add_account(mint_b, mint_account()?)
+        .add_account(vault_a, token_account(mint_a, vault_a, 0)?)
+        .add_account(vault_b, token_account(mint_b, vault_b, 0)?)
+        .add_account(exchange_booth, rent_exempt_account(legacy_data, &exchange_booth_program_id))
+        .start();
+    let rpc_client = test_validator.get_rpc_client();
+
+    let blockhash = rpc_client.get_latest_blockhash()?;
+    let mut pause_tx = Transaction::new_signed_with_payer(
+        &[
+            // PAUSE (outdated layout)
+            instruction::set_paused(
+                &exchange_booth_program_id,
+                &exchange_booth,
+                &admin.pubkey(),
+                true,
+            ),
+        ],
+        Some(&admin.pubkey()),
+        &vec![&admin],
+        blockhash,
+    );
+    pause_tx.sign(&vec![&admin], blockhash);
+    assert_exchange_booth_error(&rpc_client, &pause_tx, ExchangeBoothError::OutdatedExchangeBooth);
+
+    let blockhash = rpc_client.get_latest_blockhash()?;
+    let mut migrate_tx = Transaction::new_signed_with_payer(
+        &[
+            // MIGRATE (fee of 100%)
+            instruction::migrate_exchange_booth(
+                &exchange_booth_program_id,
+                &exchange_booth,
+                &admin.pubkey(),
+                &mint_a,
+                &mint_b,
+                pricing::BPS,
+                100,
+                &Pubkey::new_unique(),
+                &admin.pubkey(),
+            ),
+        ],
+        Some(&admin.pubkey()),
+        &vec![&admin],
+        blockhash,
+    );
+    migrate_tx.sign(&vec![&admin], blockhash);
+    assert_exchange_booth_error(&rpc_client, &migrate_tx, ExchangeBoothError::InvalidInstructionInput);
+
+    let migrate_ix = instruction::migrate_exchange_booth(
+        &exchange_booth_program_id,
+        &exchange_booth,
+        &admin.pubkey(),
+        &mint_a,
+        &mint_b,
+        100,
+        100,
+        &Pubkey::new_unique(),
+        &admin.pubkey(),
+    );
+    let blockhash = rpc_client.get_latest_blockhash()?;
+    let mut migrate_tx = Transaction::new_signed_with_payer(
+        &[migrate_ix.clone()],
+        Some(&admin.pubkey()),
+        &vec![&admin],
+        blockhash,
+    );
+    migrate_tx.sign(&vec![&admin], blockhash);
+    rpc_client.send_and_confirm_transaction(&migrate_tx)?;
+
+    println!("--- Migrate ---");
+    let exchange_booth_account = rpc_client.get_account(&exchange_booth)?;
+    let exchange_booth_data: ExchangeBooth = try_from_slice_unchecked(&exchange_booth_account.data)?;
+    println!("{:?}", exchange_booth_data);
+    println!();
+    assert_eq!(exchange_booth_account.data.len(), ExchangeBooth::LEN);
+    assert!(exchange_booth_account.lamports >= Rent::default().minimum_balance(ExchangeBooth::LEN));
+    assert_eq!(exchange_booth_data.version, ExchangeBooth::VERSION);
+    assert_eq!(exchange_booth_data.admin, admin.pubkey());
+    assert_eq!(exchange_booth_data.vault_a, vault_a);
+    assert_eq!(exchange_booth_data.vault_b, vault_b);
+    assert_eq!(exchange_booth_data.oracle, oracle);
+    assert_eq!(exchange_booth_data.bump, exchange_booth_bump);
+    assert_eq!(exchange_booth_data.vault_a_bump, vault_a_bump);
+    assert_eq!(exchange_booth_data.vault_b_bump, vault_b_bump);
+    assert_eq!(exchange_booth_data.fee_bps, 100);
+
+    // a migrated booth is in the latest layout, so migrating again fails and admin ixs work
+    let blockhash = rpc_client.get_latest_blockhash()?;
+    let mut migrate_tx = Transaction::new_signed_with_payer(
+        &[migrate_ix],
+        Some(&admin.pubkey()),
+        &vec![&admin],
+        blockhash,
+    );
+    migrate_tx.sign(&vec![&admin], blockhash);
+    assert_exchange_booth_error(&rpc_client, &migrate_tx, ExchangeBoothError::AccountAlreadyInitialized);
+
+    pause_tx.sign(&vec![&admin], blockhash);
+    rpc_client.send_and_confirm_transaction(&pause_tx)?;
+    let exchange_booth_data: ExchangeBooth = try_from_slice_unchecked(&rpc_client.get_account(&exchange_booth)?.data)?;
+    assert!(exchange_booth_data.is_paused());
+    Ok(())
+}